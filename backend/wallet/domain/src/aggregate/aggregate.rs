@@ -1,7 +1,20 @@
 use crate::entity;
 use crate::entity::Entity;
+use crate::event::WalletEvent;
 
-pub trait AggregateRoot: Entity {}
+/// An entity that is loaded and saved as a whole, raising events while it handles commands.
+pub trait AggregateRoot: Entity {
+    type Event;
+
+    /// Hands over the events raised since the aggregate was loaded.
+    fn take_events(&mut self) -> Vec<Self::Event>;
+}
 
 pub type Wallet = entity::Wallet;
-impl AggregateRoot for Wallet {}
+impl AggregateRoot for Wallet {
+    type Event = WalletEvent;
+
+    fn take_events(&mut self) -> Vec<Self::Event> {
+        Wallet::take_events(self)
+    }
+}
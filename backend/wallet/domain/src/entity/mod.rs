@@ -1,5 +1,7 @@
 mod base;
+mod transaction;
 mod wallet;
 
 pub use base::*;
+pub use transaction::*;
 pub use wallet::*;
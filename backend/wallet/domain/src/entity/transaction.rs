@@ -0,0 +1,85 @@
+use crate::entity::{Entity, Wallet};
+use crate::vo::{Id, Memo, Money, TransactionKind};
+use derive_builder::Builder;
+use getset::Getters;
+use std::time::SystemTime;
+
+/// An entry of a wallet's ledger, recording one change of its balance.
+#[derive(Clone, Debug, Getters, Builder, Eq)]
+#[builder(setter(into))]
+pub struct Transaction {
+    #[getset(get = "pub")]
+    id: Id<Transaction>,
+
+    #[getset(get = "pub")]
+    wallet_id: Id<Wallet>,
+
+    #[getset(get = "pub")]
+    kind: TransactionKind,
+
+    /// Always positive. `kind` tells whether it was added to or taken from the balance.
+    #[getset(get = "pub")]
+    amount: Money,
+
+    /// Balance of the wallet right after the transaction.
+    #[getset(get = "pub")]
+    balance: Money,
+
+    /// The other wallet of a transfer.
+    #[builder(default)]
+    #[getset(get = "pub")]
+    counterparty: Option<Id<Wallet>>,
+
+    #[builder(default)]
+    #[getset(get = "pub")]
+    memo: Memo,
+
+    #[builder(default = "SystemTime::now()")]
+    #[getset(get = "pub")]
+    create_at: SystemTime,
+}
+
+impl Entity for Transaction {
+    type Id = Id<Transaction>;
+
+    fn id(&self) -> Self::Id {
+        self.id.clone()
+    }
+}
+
+impl PartialEq for Transaction {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transaction() {
+        let transaction = TransactionBuilder::default()
+            .id("01F8MECHZX3TBDSZ7XRADM79XA"
+                .parse::<Id<Transaction>>()
+                .unwrap())
+            .wallet_id("01F8MECHZX3TBDSZ7XRADM79XE".parse::<Id<Wallet>>().unwrap())
+            .kind(TransactionKind::Deposit)
+            .amount("500 JPY".parse::<Money>().unwrap())
+            .balance("1500 JPY".parse::<Money>().unwrap())
+            .build()
+            .unwrap();
+
+        assert_eq!(transaction.counterparty(), &None);
+        assert_eq!(transaction.memo(), &Memo::default());
+
+        // err
+        assert!(TransactionBuilder::default()
+            .id("01F8MECHZX3TBDSZ7XRADM79XA"
+                .parse::<Id<Transaction>>()
+                .unwrap())
+            .kind(TransactionKind::Deposit)
+            .build()
+            .is_err());
+    }
+}
@@ -1,6 +1,9 @@
-use crate::entity::Entity;
+use crate::entity::{Entity, Transaction, TransactionBuilder};
+use crate::error::{Error, Result};
+use crate::event::WalletEvent;
+use crate::ledger::{Account, JournalEntry};
 use crate::vo::Id;
-use crate::vo::{Money, JPY};
+use crate::vo::{Currency, Memo, Money, Owner, TransactionKind, Version};
 use derive_builder::Builder;
 use getset::{Getters, Setters};
 
@@ -11,8 +14,277 @@ pub struct Wallet {
     #[getset(get = "pub")]
     id: Id<Wallet>,
 
+    #[getset(get = "pub")]
+    owner: Owner,
+
+    /// Changed only along with a journal entry, by a deposit, a withdrawal or a transfer.
+    #[getset(get = "pub")]
+    balance: Money,
+
+    #[builder(default)]
     #[getset(get = "pub", set = "pub")]
-    balance: Money<JPY>,
+    version: Version,
+
+    /// Ledger entries recorded since the wallet was loaded, persisted along with its next write.
+    #[builder(default)]
+    #[getset(get = "pub")]
+    transactions: Vec<Transaction>,
+
+    /// Journal entries backing `transactions`, which is where the stored balance comes from.
+    #[builder(default)]
+    #[getset(get = "pub")]
+    journal_entries: Vec<JournalEntry>,
+
+    /// Events raised since the wallet was loaded, queued by the repository in the same write
+    /// and published once the wallet has been saved.
+    #[builder(default)]
+    #[getset(get = "pub")]
+    events: Vec<WalletEvent>,
+}
+
+impl Wallet {
+    /// Opens an empty wallet, raising `Created`.
+    pub fn create(id: Id<Wallet>, owner: Owner, currency: Currency) -> Self {
+        let mut wallet = WalletBuilder::default()
+            .id(id.clone())
+            .owner(owner.clone())
+            .balance(Money::zero(currency))
+            .build()
+            .unwrap();
+        wallet.events.push(WalletEvent::Created {
+            id,
+            owner,
+            currency,
+        });
+
+        wallet
+    }
+
+    /// Marks the wallet for deletion. The repository decides when it is actually gone.
+    pub fn delete(&mut self) {
+        self.events.push(WalletEvent::Deleted {
+            id: self.id.clone(),
+        });
+    }
+
+    pub fn undelete(&mut self) {
+        self.events.push(WalletEvent::Undeleted {
+            id: self.id.clone(),
+        });
+    }
+
+    /// Adds `amount` from outside the ledger's wallets, recording it as transaction `id`.
+    pub fn deposit(&mut self, id: Id<Transaction>, amount: Money, memo: Memo) -> Result<(), Error> {
+        let balance = self.credited(amount)?;
+        let entry = JournalEntry::transfer(
+            id.cast(),
+            Account::Funding(amount.currency()),
+            Account::Wallet(self.id.clone()),
+            amount,
+            memo.clone(),
+        )?;
+
+        let transaction =
+            self.transaction(id, TransactionKind::Deposit, amount, balance, None, memo);
+        self.record(transaction, Some(entry));
+
+        Ok(())
+    }
+
+    /// Takes `amount` out of the ledger's wallets, recording it as transaction `id`.
+    pub fn withdraw(
+        &mut self,
+        id: Id<Transaction>,
+        amount: Money,
+        memo: Memo,
+    ) -> Result<(), Error> {
+        let balance = self.debited(amount)?;
+        let entry = JournalEntry::transfer(
+            id.cast(),
+            Account::Wallet(self.id.clone()),
+            Account::Funding(amount.currency()),
+            amount,
+            memo.clone(),
+        )?;
+
+        let transaction =
+            self.transaction(id, TransactionKind::Withdraw, amount, balance, None, memo);
+        self.record(transaction, Some(entry));
+
+        Ok(())
+    }
+
+    /// The sending half of a transfer to `to`, which is journaled once, here, under the id of
+    /// this `TransferOut` transaction.
+    pub(crate) fn transfer_out(
+        &mut self,
+        id: Id<Transaction>,
+        to: &Id<Wallet>,
+        amount: Money,
+        memo: Memo,
+    ) -> Result<(), Error> {
+        let balance = self.debited(amount)?;
+        let entry = JournalEntry::transfer(
+            id.cast(),
+            Account::Wallet(self.id.clone()),
+            Account::Wallet(to.clone()),
+            amount,
+            memo.clone(),
+        )?;
+
+        let transaction = self.transaction(
+            id,
+            TransactionKind::TransferOut,
+            amount,
+            balance,
+            Some(to.clone()),
+            memo,
+        );
+        self.record(transaction, Some(entry));
+
+        Ok(())
+    }
+
+    /// The receiving half of a transfer from `from`, whose journal entry is the sender's.
+    pub(crate) fn transfer_in(
+        &mut self,
+        id: Id<Transaction>,
+        from: &Id<Wallet>,
+        amount: Money,
+        memo: Memo,
+    ) -> Result<(), Error> {
+        let balance = self.credited(amount)?;
+
+        let transaction = self.transaction(
+            id,
+            TransactionKind::TransferIn,
+            amount,
+            balance,
+            Some(from.clone()),
+            memo,
+        );
+        self.record(transaction, None);
+
+        Ok(())
+    }
+
+    fn credited(&self, amount: Money) -> Result<Money, Error> {
+        if !amount.is_positive() {
+            return Err(Error::invalid_argument("amount", "amount must be positive"));
+        }
+
+        Ok(self.balance.checked_add(&amount)?)
+    }
+
+    fn debited(&self, amount: Money) -> Result<Money, Error> {
+        if !amount.is_positive() {
+            return Err(Error::invalid_argument("amount", "amount must be positive"));
+        }
+
+        let balance = self.balance.checked_sub(&amount)?;
+        if balance.is_negative() {
+            return Err(Error::failed_precondition(
+                "INSUFFICIENT_BALANCE",
+                format!("{} has insufficient balance", self.id),
+            ));
+        }
+
+        Ok(balance)
+    }
+
+    fn transaction(
+        &self,
+        id: Id<Transaction>,
+        kind: TransactionKind,
+        amount: Money,
+        balance: Money,
+        counterparty: Option<Id<Wallet>>,
+        memo: Memo,
+    ) -> Transaction {
+        TransactionBuilder::default()
+            .id(id)
+            .wallet_id(self.id.clone())
+            .kind(kind)
+            .amount(amount)
+            .balance(balance)
+            .counterparty(counterparty)
+            .memo(memo)
+            .build()
+            .unwrap()
+    }
+
+    // moves the balance to where `transaction` left it, keeping the transaction and the journal
+    // entry that explain the change
+    fn record(&mut self, transaction: Transaction, entry: Option<JournalEntry>) {
+        self.balance = *transaction.balance();
+        self.events.push(WalletEvent::from(&transaction));
+        self.transactions.push(transaction);
+        self.journal_entries.extend(entry);
+    }
+
+    /// Hands the recorded transactions over to be persisted.
+    pub fn take_transactions(&mut self) -> Vec<Transaction> {
+        std::mem::take(&mut self.transactions)
+    }
+
+    /// Hands the journal entries over to be posted along with the transactions.
+    pub fn take_journal_entries(&mut self) -> Vec<JournalEntry> {
+        std::mem::take(&mut self.journal_entries)
+    }
+
+    /// Hands the raised events over to be published after the wallet is saved.
+    pub fn take_events(&mut self) -> Vec<WalletEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Rebuilds a wallet from its events, the first of which must be `Created`. Deletion is
+    /// tracked by the repository, so `Deleted` and `Undeleted` leave the wallet as it is.
+    /// Replaying raises no events of its own.
+    pub fn replay<'a>(events: impl IntoIterator<Item = &'a WalletEvent>) -> Result<Self, Error> {
+        let mut events = events.into_iter();
+        let mut wallet = match events.next() {
+            Some(WalletEvent::Created {
+                id,
+                owner,
+                currency,
+            }) => WalletBuilder::default()
+                .id(id.clone())
+                .owner(owner.clone())
+                .balance(Money::zero(*currency))
+                .build()
+                .unwrap(),
+            event => {
+                return Err(Error::internal(anyhow::anyhow!(
+                    "a wallet must start with a created event, got {:?}",
+                    event
+                )))
+            }
+        };
+
+        for event in events {
+            wallet.apply(event)?;
+        }
+
+        Ok(wallet)
+    }
+
+    fn apply(&mut self, event: &WalletEvent) -> Result<(), Error> {
+        match event {
+            WalletEvent::Created { .. } => Err(Error::internal(anyhow::anyhow!(
+                "{} was created twice",
+                self.id
+            ))),
+            WalletEvent::Deposited { amount, .. } => {
+                self.balance = self.credited(*amount)?;
+                Ok(())
+            }
+            WalletEvent::Withdrawn { amount, .. } => {
+                self.balance = self.debited(*amount)?;
+                Ok(())
+            }
+            WalletEvent::Deleted { .. } | WalletEvent::Undeleted { .. } => Ok(()),
+        }
+    }
 }
 
 impl Entity for Wallet {
@@ -32,6 +304,8 @@ impl PartialEq for Wallet {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ledger::Posting;
+    use crate::vo::Currency;
 
     #[test]
     fn test_wallet() {
@@ -39,7 +313,8 @@ mod tests {
             .id("01F8MECHZX3TBDSZ7XRADM79XE"
                 .parse::<Id::<Wallet>>()
                 .unwrap())
-            .balance("1000".parse::<Money<JPY>>().unwrap())
+            .owner("alice".parse::<Owner>().unwrap())
+            .balance("1000 JPY".parse::<Money>().unwrap())
             .build()
             .is_ok());
     }
@@ -48,16 +323,24 @@ mod tests {
     fn test_wallet_eq() {
         let before = WalletBuilder::default()
             .id("01F8MECHZX3TBDSZ7XRADM79XE".parse::<Id<Wallet>>().unwrap())
-            .balance("1000".parse::<Money<JPY>>().unwrap())
+            .owner("alice".parse::<Owner>().unwrap())
+            .balance("1000 JPY".parse::<Money>().unwrap())
             .build()
             .unwrap();
 
         let mut after = before.clone();
-        after.set_balance("1000".parse::<Money<JPY>>().unwrap());
+        after
+            .deposit(
+                "01F8MECHZX3TBDSZ7XRADM79XA".parse().unwrap(),
+                "500 JPY".parse::<Money>().unwrap(),
+                Memo::default(),
+            )
+            .unwrap();
 
         let another = WalletBuilder::default()
             .id("01F8MECHZX3TBDSZ7XRADM79XF".parse::<Id<Wallet>>().unwrap())
-            .balance("1000".parse::<Money<JPY>>().unwrap())
+            .owner("alice".parse::<Owner>().unwrap())
+            .balance("1000 JPY".parse::<Money>().unwrap())
             .build()
             .unwrap();
 
@@ -65,4 +348,187 @@ mod tests {
         assert_ne!(before, another);
         assert_ne!(after, another);
     }
+
+    #[test]
+    fn test_wallet_events() {
+        let id = "01F8MECHZX3TBDSZ7XRADM79XE".parse::<Id<Wallet>>().unwrap();
+        let mut wallet =
+            Wallet::create(id.clone(), "alice".parse::<Owner>().unwrap(), Currency::JPY);
+        assert_eq!(wallet.balance(), &"0 JPY".parse::<Money>().unwrap());
+
+        wallet.delete();
+        wallet.undelete();
+
+        assert_eq!(
+            wallet.take_events(),
+            vec![
+                WalletEvent::Created {
+                    id: id.clone(),
+                    owner: "alice".parse::<Owner>().unwrap(),
+                    currency: Currency::JPY,
+                },
+                WalletEvent::Deleted { id: id.clone() },
+                WalletEvent::Undeleted { id },
+            ]
+        );
+        assert!(wallet.events().is_empty());
+    }
+
+    fn new_transaction_id() -> Id<Transaction> {
+        "01F8MECHZX3TBDSZ7XRADM79XA".parse().unwrap()
+    }
+
+    #[test]
+    fn test_wallet_deposit() {
+        let mut wallet = WalletBuilder::default()
+            .id("01F8MECHZX3TBDSZ7XRADM79XE".parse::<Id<Wallet>>().unwrap())
+            .owner("alice".parse::<Owner>().unwrap())
+            .balance("1000 JPY".parse::<Money>().unwrap())
+            .build()
+            .unwrap();
+
+        let amount = "500 JPY".parse::<Money>().unwrap();
+        assert!(wallet
+            .deposit(new_transaction_id(), amount, "salary".parse().unwrap())
+            .is_ok());
+        assert_eq!(wallet.balance(), &"1500 JPY".parse::<Money>().unwrap());
+
+        // the balance, the transaction, the journal entry and the event change together
+        let transactions = wallet.take_transactions();
+        assert_eq!(transactions.len(), 1);
+        assert_eq!(transactions[0].kind(), &TransactionKind::Deposit);
+        assert_eq!(transactions[0].wallet_id(), wallet.id());
+        assert_eq!(
+            transactions[0].balance(),
+            &"1500 JPY".parse::<Money>().unwrap()
+        );
+        assert!(wallet.transactions().is_empty());
+
+        let entries = wallet.take_journal_entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(
+            entries[0].postings(),
+            &vec![
+                Posting::debit(Account::Funding(Currency::JPY), amount),
+                Posting::credit(Account::Wallet(wallet.id().clone()), amount),
+            ]
+        );
+        assert!(wallet.journal_entries().is_empty());
+
+        assert_eq!(wallet.events(), &vec![WalletEvent::from(&transactions[0])]);
+
+        // err
+        for amount in ["0 JPY", "-1 JPY"] {
+            assert!(wallet
+                .deposit(
+                    new_transaction_id(),
+                    amount.parse().unwrap(),
+                    Memo::default()
+                )
+                .is_err());
+        }
+        assert!(wallet
+            .deposit(
+                new_transaction_id(),
+                "1 USD".parse().unwrap(),
+                Memo::default()
+            )
+            .is_err());
+        assert_eq!(wallet.balance(), &"1500 JPY".parse::<Money>().unwrap());
+        assert!(wallet.transactions().is_empty());
+        assert!(wallet.journal_entries().is_empty());
+    }
+
+    #[test]
+    fn test_wallet_withdraw() {
+        let mut wallet = WalletBuilder::default()
+            .id("01F8MECHZX3TBDSZ7XRADM79XE".parse::<Id<Wallet>>().unwrap())
+            .owner("alice".parse::<Owner>().unwrap())
+            .balance("1000 JPY".parse::<Money>().unwrap())
+            .build()
+            .unwrap();
+        let withdraw = |wallet: &mut Wallet, amount: &str| {
+            wallet.withdraw(
+                new_transaction_id(),
+                amount.parse().unwrap(),
+                Memo::default(),
+            )
+        };
+
+        assert!(withdraw(&mut wallet, "400 JPY").is_ok());
+        assert_eq!(wallet.balance(), &"600 JPY".parse::<Money>().unwrap());
+        assert!(withdraw(&mut wallet, "600 JPY").is_ok());
+        assert_eq!(wallet.balance(), &"0 JPY".parse::<Money>().unwrap());
+
+        let amount = "600 JPY".parse::<Money>().unwrap();
+        assert_eq!(wallet.transactions().len(), 2);
+        assert_eq!(wallet.transactions()[1].kind(), &TransactionKind::Withdraw);
+        assert_eq!(
+            wallet.transactions()[1].balance(),
+            &"0 JPY".parse::<Money>().unwrap()
+        );
+        assert_eq!(
+            wallet.journal_entries()[1].postings(),
+            &vec![
+                Posting::debit(Account::Wallet(wallet.id().clone()), amount),
+                Posting::credit(Account::Funding(Currency::JPY), amount),
+            ]
+        );
+        assert_eq!(wallet.events().len(), 2);
+
+        // err
+        assert_eq!(
+            withdraw(&mut wallet, "1 JPY").unwrap_err().reason(),
+            "INSUFFICIENT_BALANCE"
+        );
+        assert!(withdraw(&mut wallet, "0 JPY").is_err());
+        assert!(withdraw(&mut wallet, "-1 JPY").is_err());
+        assert!(withdraw(&mut wallet, "1 USD").is_err());
+        assert_eq!(wallet.balance(), &"0 JPY".parse::<Money>().unwrap());
+        assert_eq!(wallet.transactions().len(), 2);
+        assert_eq!(wallet.journal_entries().len(), 2);
+    }
+
+    #[test]
+    fn test_wallet_replay() {
+        let id = "01F8MECHZX3TBDSZ7XRADM79XE".parse::<Id<Wallet>>().unwrap();
+        let deposited = |amount: &str| WalletEvent::Deposited {
+            id: id.clone(),
+            transaction_id: "01F8MECHZX3TBDSZ7XRADM79XA".parse().unwrap(),
+            amount: amount.parse().unwrap(),
+            counterparty: None,
+            memo: Memo::default(),
+        };
+        let created = WalletEvent::Created {
+            id: id.clone(),
+            owner: "alice".parse().unwrap(),
+            currency: Currency::JPY,
+        };
+
+        let wallet = Wallet::replay(&[
+            created.clone(),
+            deposited("1000 JPY"),
+            WalletEvent::Withdrawn {
+                id: id.clone(),
+                transaction_id: "01F8MECHZX3TBDSZ7XRADM79XB".parse().unwrap(),
+                amount: "300 JPY".parse().unwrap(),
+                counterparty: None,
+                memo: Memo::default(),
+            },
+            WalletEvent::Deleted { id: id.clone() },
+            WalletEvent::Undeleted { id: id.clone() },
+        ])
+        .unwrap();
+
+        assert_eq!(wallet.id(), &id);
+        assert_eq!(wallet.owner(), &"alice".parse::<Owner>().unwrap());
+        assert_eq!(wallet.balance(), &"700 JPY".parse::<Money>().unwrap());
+        assert!(wallet.transactions().is_empty());
+        assert!(wallet.events().is_empty());
+
+        // err
+        assert!(Wallet::replay(&[]).is_err());
+        assert!(Wallet::replay(&[deposited("1000 JPY")]).is_err());
+        assert!(Wallet::replay(&[created.clone(), created]).is_err());
+    }
 }
@@ -0,0 +1,161 @@
+use crate::ledger::LedgerError;
+use crate::repository::ConcurrencyError;
+use crate::vo::{IdError, MemoError, MoneyError, OwnerError, VersionError};
+use std::fmt;
+use thiserror::Error as ThisError;
+
+/// Failure classes shared by every layer. Callers match on the variant to decide how to answer,
+/// while `reason` names the specific cause in UPPER_SNAKE_CASE.
+#[derive(ThisError, Debug)]
+pub enum Error {
+    #[error("{resource} {id} was not found")]
+    NotFound { resource: &'static str, id: String },
+    #[error("{field}: {description}")]
+    InvalidArgument {
+        field: &'static str,
+        description: String,
+    },
+    /// The write raced with another one and may succeed when retried from a fresh read.
+    #[error("{message}")]
+    Conflict {
+        reason: &'static str,
+        message: String,
+    },
+    /// The system is not in a state that allows the operation, and retrying will not help.
+    #[error("{message}")]
+    FailedPrecondition {
+        reason: &'static str,
+        message: String,
+    },
+    #[error("service is unavailable: {0}")]
+    Unavailable(#[source] anyhow::Error),
+    #[error("internal error: {0}")]
+    Internal(#[source] anyhow::Error),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+impl Error {
+    pub fn not_found(resource: &'static str, id: impl fmt::Display) -> Self {
+        Self::NotFound {
+            resource,
+            id: id.to_string(),
+        }
+    }
+
+    pub fn invalid_argument(field: &'static str, description: impl fmt::Display) -> Self {
+        Self::InvalidArgument {
+            field,
+            description: description.to_string(),
+        }
+    }
+
+    pub fn conflict(reason: &'static str, message: impl fmt::Display) -> Self {
+        Self::Conflict {
+            reason,
+            message: message.to_string(),
+        }
+    }
+
+    pub fn failed_precondition(reason: &'static str, message: impl fmt::Display) -> Self {
+        Self::FailedPrecondition {
+            reason,
+            message: message.to_string(),
+        }
+    }
+
+    pub fn unavailable(e: impl Into<anyhow::Error>) -> Self {
+        Self::Unavailable(e.into())
+    }
+
+    pub fn internal(e: impl Into<anyhow::Error>) -> Self {
+        Self::Internal(e.into())
+    }
+
+    pub fn reason(&self) -> &'static str {
+        match self {
+            Self::NotFound { .. } => "NOT_FOUND",
+            Self::InvalidArgument { .. } => "INVALID_ARGUMENT",
+            Self::Conflict { reason, .. } => reason,
+            Self::FailedPrecondition { reason, .. } => reason,
+            Self::Unavailable(_) => "UNAVAILABLE",
+            Self::Internal(_) => "INTERNAL",
+        }
+    }
+}
+
+impl From<IdError> for Error {
+    fn from(e: IdError) -> Self {
+        Self::invalid_argument("id", e)
+    }
+}
+
+impl From<OwnerError> for Error {
+    fn from(e: OwnerError) -> Self {
+        Self::invalid_argument("owner", e)
+    }
+}
+
+impl From<MemoError> for Error {
+    fn from(e: MemoError) -> Self {
+        Self::invalid_argument("memo", e)
+    }
+}
+
+impl From<MoneyError> for Error {
+    fn from(e: MoneyError) -> Self {
+        match e {
+            MoneyError::Overflow => Self::failed_precondition("AMOUNT_OVERFLOW", e),
+            _ => Self::invalid_argument("amount", e),
+        }
+    }
+}
+
+// entries are only ever built from amounts the aggregate already validated, so one that does
+// not balance is a bug rather than a bad request
+impl From<LedgerError> for Error {
+    fn from(e: LedgerError) -> Self {
+        match e {
+            LedgerError::Money(e) => e.into(),
+            _ => Self::internal(e),
+        }
+    }
+}
+
+impl From<VersionError> for Error {
+    fn from(e: VersionError) -> Self {
+        Self::invalid_argument("version", e)
+    }
+}
+
+impl From<ConcurrencyError> for Error {
+    fn from(e: ConcurrencyError) -> Self {
+        Self::conflict("VERSION_CONFLICT", e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error() {
+        let e = Error::not_found("wallet", "01F8MECHZX3TBDSZ7XRADM79XE");
+        assert_eq!(
+            e.to_string(),
+            "wallet 01F8MECHZX3TBDSZ7XRADM79XE was not found"
+        );
+        assert_eq!(e.reason(), "NOT_FOUND");
+
+        let e = Error::invalid_argument("amount", "must be positive");
+        assert_eq!(e.to_string(), "amount: must be positive");
+        assert_eq!(e.reason(), "INVALID_ARGUMENT");
+
+        let e = Error::failed_precondition("INSUFFICIENT_BALANCE", "insufficient balance");
+        assert_eq!(e.to_string(), "insufficient balance");
+        assert_eq!(e.reason(), "INSUFFICIENT_BALANCE");
+
+        let e = Error::internal(anyhow::anyhow!("boom"));
+        assert_eq!(e.reason(), "INTERNAL");
+    }
+}
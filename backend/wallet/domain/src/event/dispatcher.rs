@@ -0,0 +1,120 @@
+use crate::error::{Error, Result};
+use std::sync::{Arc, RwLock};
+
+/// Publishes the events of aggregates that have been saved.
+pub trait EventPublisher<E>: Send + Sync {
+    fn publish(&self, events: Vec<E>);
+}
+
+/// Reacts to published events, e.g. to notify someone or keep a projection up to date.
+pub trait EventSubscriber<E>: Send + Sync {
+    fn handle(&self, event: &E) -> Result<(), Error>;
+}
+
+/// Delivers events in-process to every subscriber, in the order they subscribed.
+///
+/// The events are published after the change they describe has been saved, so a subscriber
+/// that fails cannot undo it: the failure is reported and the remaining subscribers still get
+/// the event.
+pub struct EventDispatcher<E> {
+    subscribers: Arc<RwLock<Vec<Arc<dyn EventSubscriber<E>>>>>,
+}
+
+impl<E> EventDispatcher<E> {
+    pub fn new() -> Self {
+        Self {
+            subscribers: Arc::new(RwLock::new(vec![])),
+        }
+    }
+
+    pub fn subscribe<S>(&self, subscriber: S)
+    where
+        S: EventSubscriber<E> + 'static,
+    {
+        self.subscribers.write().unwrap().push(Arc::new(subscriber));
+    }
+}
+
+impl<E> Default for EventDispatcher<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E> Clone for EventDispatcher<E> {
+    fn clone(&self) -> Self {
+        Self {
+            subscribers: self.subscribers.clone(),
+        }
+    }
+}
+
+impl<E> EventPublisher<E> for EventDispatcher<E>
+where
+    E: std::fmt::Debug,
+{
+    fn publish(&self, events: Vec<E>) {
+        // subscribers may subscribe others, so they are called without holding the lock
+        let subscribers = self.subscribers.read().unwrap().clone();
+        for event in &events {
+            for subscriber in &subscribers {
+                if let Err(e) = subscriber.handle(event) {
+                    tracing::error!(event = ?event, error = %e, "failed to handle an event");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    struct RecordingSubscriber {
+        name: &'static str,
+        received: Arc<Mutex<Vec<String>>>,
+        fail: bool,
+    }
+
+    impl EventSubscriber<u32> for RecordingSubscriber {
+        fn handle(&self, event: &u32) -> Result<(), Error> {
+            self.received
+                .lock()
+                .unwrap()
+                .push(format!("{}:{}", self.name, event));
+            if self.fail {
+                return Err(Error::internal(anyhow::anyhow!("{} failed", self.name)));
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_event_dispatcher_publish() {
+        let received = Arc::new(Mutex::new(vec![]));
+        let sut = EventDispatcher::new();
+        sut.clone().subscribe(RecordingSubscriber {
+            name: "audit",
+            received: received.clone(),
+            fail: true,
+        });
+        sut.subscribe(RecordingSubscriber {
+            name: "projection",
+            received: received.clone(),
+            fail: false,
+        });
+
+        sut.publish(vec![1, 2]);
+
+        // a failing subscriber does not keep the event from the others
+        assert_eq!(
+            *received.lock().unwrap(),
+            vec!["audit:1", "projection:1", "audit:2", "projection:2"]
+        );
+
+        // nobody listening is fine
+        EventDispatcher::<u32>::new().publish(vec![3]);
+    }
+}
@@ -0,0 +1,5 @@
+mod dispatcher;
+mod wallet;
+
+pub use dispatcher::*;
+pub use wallet::*;
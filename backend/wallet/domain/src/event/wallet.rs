@@ -0,0 +1,124 @@
+use crate::entity::{Transaction, Wallet};
+use crate::vo::{Currency, Id, Memo, Money, Owner};
+
+/// Something that happened to a wallet. Replaying a wallet's events in order rebuilds it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WalletEvent {
+    Created {
+        id: Id<Wallet>,
+        owner: Owner,
+        currency: Currency,
+    },
+    Deposited {
+        id: Id<Wallet>,
+        transaction_id: Id<Transaction>,
+        amount: Money,
+        /// The wallet the money came from when it was transferred.
+        counterparty: Option<Id<Wallet>>,
+        memo: Memo,
+    },
+    Withdrawn {
+        id: Id<Wallet>,
+        transaction_id: Id<Transaction>,
+        amount: Money,
+        /// The wallet the money went to when it was transferred.
+        counterparty: Option<Id<Wallet>>,
+        memo: Memo,
+    },
+    Deleted {
+        id: Id<Wallet>,
+    },
+    Undeleted {
+        id: Id<Wallet>,
+    },
+}
+
+impl WalletEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Created { .. } => "WalletCreated",
+            Self::Deposited { .. } => "WalletDeposited",
+            Self::Withdrawn { .. } => "WalletWithdrawn",
+            Self::Deleted { .. } => "WalletDeleted",
+            Self::Undeleted { .. } => "WalletUndeleted",
+        }
+    }
+
+    /// The wallet the event happened to.
+    pub fn id(&self) -> &Id<Wallet> {
+        match self {
+            Self::Created { id, .. }
+            | Self::Deposited { id, .. }
+            | Self::Withdrawn { id, .. }
+            | Self::Deleted { id }
+            | Self::Undeleted { id } => id,
+        }
+    }
+}
+
+impl From<&Transaction> for WalletEvent {
+    fn from(transaction: &Transaction) -> Self {
+        let id = transaction.wallet_id().clone();
+        let transaction_id = transaction.id().clone();
+        let amount = *transaction.amount();
+        let counterparty = transaction.counterparty().clone();
+        let memo = transaction.memo().clone();
+
+        if transaction.kind().is_credit() {
+            Self::Deposited {
+                id,
+                transaction_id,
+                amount,
+                counterparty,
+                memo,
+            }
+        } else {
+            Self::Withdrawn {
+                id,
+                transaction_id,
+                amount,
+                counterparty,
+                memo,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::TransactionBuilder;
+    use crate::vo::TransactionKind;
+
+    #[test]
+    fn test_wallet_event_from_transaction() {
+        let transaction = TransactionBuilder::default()
+            .id("01F8MECHZX3TBDSZ7XRADM79XA"
+                .parse::<Id<Transaction>>()
+                .unwrap())
+            .wallet_id("01F8MECHZX3TBDSZ7XRADM79XE".parse::<Id<Wallet>>().unwrap())
+            .kind(TransactionKind::TransferOut)
+            .amount("300 JPY".parse::<Money>().unwrap())
+            .balance("700 JPY".parse::<Money>().unwrap())
+            .counterparty(Some(
+                "01F8MECHZX3TBDSZ7XRADM79XF".parse::<Id<Wallet>>().unwrap(),
+            ))
+            .build()
+            .unwrap();
+
+        let event = WalletEvent::from(&transaction);
+
+        assert_eq!(event.name(), "WalletWithdrawn");
+        assert_eq!(event.id(), transaction.wallet_id());
+        assert_eq!(
+            event,
+            WalletEvent::Withdrawn {
+                id: transaction.wallet_id().clone(),
+                transaction_id: transaction.id().clone(),
+                amount: "300 JPY".parse::<Money>().unwrap(),
+                counterparty: transaction.counterparty().clone(),
+                memo: Memo::default(),
+            }
+        );
+    }
+}
@@ -0,0 +1,116 @@
+use crate::entity::Wallet;
+use crate::vo::{Currency, Id, IdError, ValueObject};
+use parse_display::{Display, FromStr};
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum AccountError {
+    #[error("invalid account: {0}")]
+    InvalidFormat(String),
+    #[error("invalid wallet account: {0}")]
+    InvalidWallet(#[from] IdError),
+}
+
+/// Which side of the books an account sits on, and so which side increases it.
+#[derive(Display, FromStr, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[display(style = "SNAKE_CASE")]
+pub enum AccountKind {
+    /// Increased by debits.
+    Asset,
+    /// Increased by credits.
+    Liability,
+}
+
+impl ValueObject for AccountKind {}
+
+/// An account of the ledger, written as `"wallet:<id>"` or `"funding:<currency>"`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Account {
+    /// What the service owes the owner of a wallet. Its balance is the wallet's balance.
+    Wallet(Id<Wallet>),
+    /// Money held outside the service on behalf of the wallets, which deposits come from and
+    /// withdrawals go to.
+    Funding(Currency),
+}
+
+impl ValueObject for Account {}
+
+impl Account {
+    pub fn kind(&self) -> AccountKind {
+        match self {
+            Self::Wallet(_) => AccountKind::Liability,
+            Self::Funding(_) => AccountKind::Asset,
+        }
+    }
+}
+
+impl FromStr for Account {
+    type Err = AccountError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            Some(("wallet", id)) => Ok(Self::Wallet(id.parse::<Id<Wallet>>()?)),
+            Some(("funding", currency)) => currency
+                .parse::<Currency>()
+                .map(Self::Funding)
+                .map_err(|_| AccountError::InvalidFormat(s.to_string())),
+            _ => Err(AccountError::InvalidFormat(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Account {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Wallet(id) => write!(f, "wallet:{}", id),
+            Self::Funding(currency) => write!(f, "funding:{}", currency),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_account_from_str() {
+        assert_eq!(
+            "wallet:01F8MECHZX3TBDSZ7XRADM79XE"
+                .parse::<Account>()
+                .unwrap(),
+            Account::Wallet("01F8MECHZX3TBDSZ7XRADM79XE".parse().unwrap())
+        );
+        assert_eq!(
+            "funding:JPY".parse::<Account>().unwrap(),
+            Account::Funding(Currency::JPY)
+        );
+
+        // err
+        assert!("wallet:hogehoge".parse::<Account>().is_err());
+        assert!("funding:XXX".parse::<Account>().is_err());
+        assert!("cash:JPY".parse::<Account>().is_err());
+        assert!("JPY".parse::<Account>().is_err());
+    }
+
+    #[test]
+    fn test_account_to_string() {
+        let account = "wallet:01F8MECHZX3TBDSZ7XRADM79XE"
+            .parse::<Account>()
+            .unwrap();
+
+        assert_eq!(account.to_string(), "wallet:01F8MECHZX3TBDSZ7XRADM79XE");
+        assert_eq!(Account::Funding(Currency::USD).to_string(), "funding:USD");
+    }
+
+    #[test]
+    fn test_account_kind() {
+        assert_eq!(
+            Account::Wallet("01F8MECHZX3TBDSZ7XRADM79XE".parse().unwrap()).kind(),
+            AccountKind::Liability
+        );
+        assert_eq!(Account::Funding(Currency::JPY).kind(), AccountKind::Asset);
+        assert_eq!(AccountKind::Liability.to_string(), "LIABILITY");
+    }
+}
@@ -0,0 +1,198 @@
+use crate::entity::Entity;
+use crate::ledger::{Account, Posting, Side};
+use crate::vo::{Currency, Id, Memo, Money, MoneyError};
+use getset::Getters;
+use std::time::SystemTime;
+use thiserror::Error;
+
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum LedgerError {
+    #[error("a journal entry needs at least two postings, got {0}")]
+    TooFewPostings(usize),
+    #[error("posting amounts must be positive, got {0}")]
+    NonPositiveAmount(Money),
+    #[error("a journal entry must be in a single currency: {0} and {1}")]
+    CurrencyMismatch(Currency, Currency),
+    #[error("debits {debit} and credits {credit} do not balance")]
+    Unbalanced { debit: Money, credit: Money },
+    #[error(transparent)]
+    Money(#[from] MoneyError),
+}
+
+/// A set of postings recorded together, whose debits and credits always sum to the same amount.
+#[derive(Clone, Debug, Getters, Eq)]
+pub struct JournalEntry {
+    #[getset(get = "pub")]
+    id: Id<JournalEntry>,
+
+    #[getset(get = "pub")]
+    postings: Vec<Posting>,
+
+    #[getset(get = "pub")]
+    memo: Memo,
+
+    #[getset(get = "pub")]
+    create_at: SystemTime,
+}
+
+impl JournalEntry {
+    pub fn new(
+        id: Id<JournalEntry>,
+        postings: Vec<Posting>,
+        memo: Memo,
+    ) -> Result<Self, LedgerError> {
+        if postings.len() < 2 {
+            return Err(LedgerError::TooFewPostings(postings.len()));
+        }
+
+        let currency = postings[0].amount().currency();
+        let mut debit = Money::zero(currency);
+        let mut credit = Money::zero(currency);
+        for posting in &postings {
+            if !posting.amount().is_positive() {
+                return Err(LedgerError::NonPositiveAmount(*posting.amount()));
+            }
+            if posting.amount().currency() != currency {
+                return Err(LedgerError::CurrencyMismatch(
+                    currency,
+                    posting.amount().currency(),
+                ));
+            }
+            match posting.side() {
+                Side::Debit => debit = debit.checked_add(posting.amount())?,
+                Side::Credit => credit = credit.checked_add(posting.amount())?,
+            }
+        }
+        if debit != credit {
+            return Err(LedgerError::Unbalanced { debit, credit });
+        }
+
+        Ok(Self {
+            id,
+            postings,
+            memo,
+            create_at: SystemTime::now(),
+        })
+    }
+
+    /// The common two-line entry moving `amount` from `credit` to `debit`.
+    pub fn transfer(
+        id: Id<JournalEntry>,
+        debit: Account,
+        credit: Account,
+        amount: Money,
+        memo: Memo,
+    ) -> Result<Self, LedgerError> {
+        Self::new(
+            id,
+            vec![
+                Posting::debit(debit, amount),
+                Posting::credit(credit, amount),
+            ],
+            memo,
+        )
+    }
+
+    pub fn currency(&self) -> Currency {
+        self.postings[0].amount().currency()
+    }
+}
+
+impl Entity for JournalEntry {
+    type Id = Id<JournalEntry>;
+
+    fn id(&self) -> Self::Id {
+        self.id.clone()
+    }
+}
+
+impl PartialEq for JournalEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id() -> Id<JournalEntry> {
+        "01F8MECHZX3TBDSZ7XRADM79XA".parse().unwrap()
+    }
+
+    fn wallet() -> Account {
+        "wallet:01F8MECHZX3TBDSZ7XRADM79XE".parse().unwrap()
+    }
+
+    #[test]
+    fn test_journal_entry() {
+        let entry = JournalEntry::new(
+            id(),
+            vec![
+                Posting::debit(Account::Funding(Currency::JPY), "1000 JPY".parse().unwrap()),
+                Posting::credit(wallet(), "600 JPY".parse().unwrap()),
+                Posting::credit(
+                    "wallet:01F8MECHZX3TBDSZ7XRADM79XF".parse().unwrap(),
+                    "400 JPY".parse().unwrap(),
+                ),
+            ],
+            Memo::default(),
+        )
+        .unwrap();
+
+        assert_eq!(entry.postings().len(), 3);
+        assert_eq!(entry.currency(), Currency::JPY);
+        assert!(entry
+            .postings()
+            .iter()
+            .map(|p| p.signed_amount())
+            .try_fold(Money::zero(Currency::JPY), |acc, m| acc.checked_add(&m))
+            .unwrap()
+            .is_zero());
+    }
+
+    #[test]
+    fn test_journal_entry_err() {
+        let jpy = "1000 JPY".parse::<Money>().unwrap();
+
+        assert_eq!(
+            JournalEntry::new(id(), vec![Posting::debit(wallet(), jpy)], Memo::default()),
+            Err(LedgerError::TooFewPostings(1))
+        );
+        assert_eq!(
+            JournalEntry::new(
+                id(),
+                vec![
+                    Posting::debit(wallet(), jpy),
+                    Posting::credit(Account::Funding(Currency::JPY), "999 JPY".parse().unwrap()),
+                ],
+                Memo::default()
+            ),
+            Err(LedgerError::Unbalanced {
+                debit: jpy,
+                credit: "999 JPY".parse().unwrap()
+            })
+        );
+        assert_eq!(
+            JournalEntry::transfer(
+                id(),
+                wallet(),
+                Account::Funding(Currency::JPY),
+                "0 JPY".parse().unwrap(),
+                Memo::default()
+            ),
+            Err(LedgerError::NonPositiveAmount("0 JPY".parse().unwrap()))
+        );
+        assert_eq!(
+            JournalEntry::new(
+                id(),
+                vec![
+                    Posting::debit(wallet(), jpy),
+                    Posting::credit(Account::Funding(Currency::USD), "1000 USD".parse().unwrap()),
+                ],
+                Memo::default()
+            ),
+            Err(LedgerError::CurrencyMismatch(Currency::JPY, Currency::USD))
+        );
+    }
+}
@@ -0,0 +1,9 @@
+mod account;
+mod journal_entry;
+mod posting;
+mod trial_balance;
+
+pub use account::*;
+pub use journal_entry::*;
+pub use posting::*;
+pub use trial_balance::*;
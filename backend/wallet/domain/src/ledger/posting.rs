@@ -0,0 +1,78 @@
+use crate::ledger::Account;
+use crate::vo::{Money, ValueObject};
+use getset::Getters;
+use parse_display::{Display, FromStr};
+
+#[derive(Display, FromStr, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[display(style = "SNAKE_CASE")]
+pub enum Side {
+    Debit,
+    Credit,
+}
+
+impl ValueObject for Side {}
+
+/// One line of a journal entry, moving `amount` into or out of an account.
+#[derive(Clone, Debug, PartialEq, Eq, Getters)]
+pub struct Posting {
+    #[getset(get = "pub")]
+    account: Account,
+
+    #[getset(get = "pub")]
+    side: Side,
+
+    /// Always positive. `side` tells which way it moves.
+    #[getset(get = "pub")]
+    amount: Money,
+}
+
+impl ValueObject for Posting {}
+
+impl Posting {
+    pub fn debit(account: Account, amount: Money) -> Self {
+        Self {
+            account,
+            side: Side::Debit,
+            amount,
+        }
+    }
+
+    pub fn credit(account: Account, amount: Money) -> Self {
+        Self {
+            account,
+            side: Side::Credit,
+            amount,
+        }
+    }
+
+    /// The amount with debits positive and credits negative, so the postings of a balanced
+    /// entry sum to zero.
+    pub fn signed_amount(&self) -> Money {
+        match self.side {
+            Side::Debit => self.amount,
+            // negating keeps the scale, so the result is always valid money
+            Side::Credit => Money::new(-self.amount.amount(), self.amount.currency()).unwrap(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vo::Currency;
+
+    #[test]
+    fn test_posting_signed_amount() {
+        let account = Account::Funding(Currency::JPY);
+        let amount = "500 JPY".parse::<Money>().unwrap();
+
+        assert_eq!(
+            Posting::debit(account.clone(), amount).signed_amount(),
+            amount
+        );
+        assert_eq!(
+            Posting::credit(account, amount).signed_amount(),
+            "-500 JPY".parse::<Money>().unwrap()
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use crate::ledger::{Account, AccountKind, LedgerError};
+use crate::vo::{Currency, Money};
+use getset::{CopyGetters, Getters};
+
+/// Debits and credits ever posted to one account.
+#[derive(Clone, Debug, PartialEq, Eq, Getters, CopyGetters)]
+pub struct TrialBalanceLine {
+    #[getset(get = "pub")]
+    account: Account,
+
+    #[getset(get_copy = "pub")]
+    debit: Money,
+
+    #[getset(get_copy = "pub")]
+    credit: Money,
+}
+
+impl TrialBalanceLine {
+    pub fn new(account: Account, debit: Money, credit: Money) -> Result<Self, LedgerError> {
+        if debit.currency() != credit.currency() {
+            return Err(LedgerError::CurrencyMismatch(
+                debit.currency(),
+                credit.currency(),
+            ));
+        }
+
+        Ok(Self {
+            account,
+            debit,
+            credit,
+        })
+    }
+
+    pub fn currency(&self) -> Currency {
+        self.debit.currency()
+    }
+
+    /// The balance on the side that increases the account, e.g. a wallet's balance.
+    pub fn balance(&self) -> Result<Money, LedgerError> {
+        Ok(match self.account.kind() {
+            AccountKind::Asset => self.debit.checked_sub(&self.credit)?,
+            AccountKind::Liability => self.credit.checked_sub(&self.debit)?,
+        })
+    }
+}
+
+/// Total debits and credits of one currency across the whole ledger.
+#[derive(Clone, Debug, PartialEq, Eq, CopyGetters)]
+pub struct TrialBalanceTotal {
+    #[getset(get_copy = "pub")]
+    currency: Currency,
+
+    #[getset(get_copy = "pub")]
+    debit: Money,
+
+    #[getset(get_copy = "pub")]
+    credit: Money,
+}
+
+impl TrialBalanceTotal {
+    pub fn is_balanced(&self) -> bool {
+        self.debit == self.credit
+    }
+}
+
+/// Every account of the ledger with its totals. The books are in order when, for each
+/// currency, the debits of all accounts equal their credits.
+#[derive(Clone, Debug, PartialEq, Eq, Getters)]
+pub struct TrialBalance {
+    #[getset(get = "pub")]
+    lines: Vec<TrialBalanceLine>,
+
+    #[getset(get = "pub")]
+    totals: Vec<TrialBalanceTotal>,
+}
+
+impl TrialBalance {
+    pub fn new(lines: Vec<TrialBalanceLine>) -> Result<Self, LedgerError> {
+        let mut totals = Vec::<TrialBalanceTotal>::new();
+        for line in &lines {
+            match totals.iter_mut().find(|t| t.currency == line.currency()) {
+                Some(total) => {
+                    total.debit = total.debit.checked_add(&line.debit)?;
+                    total.credit = total.credit.checked_add(&line.credit)?;
+                }
+                None => totals.push(TrialBalanceTotal {
+                    currency: line.currency(),
+                    debit: line.debit,
+                    credit: line.credit,
+                }),
+            }
+        }
+
+        Ok(Self { lines, totals })
+    }
+
+    pub fn is_balanced(&self) -> bool {
+        self.totals.iter().all(|t| t.is_balanced())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(account: &str, debit: &str, credit: &str) -> TrialBalanceLine {
+        TrialBalanceLine::new(
+            account.parse().unwrap(),
+            debit.parse().unwrap(),
+            credit.parse().unwrap(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_trial_balance() {
+        let sut = TrialBalance::new(vec![
+            line("funding:JPY", "1500 JPY", "200 JPY"),
+            line("wallet:01F8MECHZX3TBDSZ7XRADM79XE", "500 JPY", "1500 JPY"),
+            line("wallet:01F8MECHZX3TBDSZ7XRADM79XF", "0 JPY", "300 JPY"),
+            line("funding:USD", "10.00 USD", "0.00 USD"),
+            line("wallet:01F8MECHZX3TBDSZ7XRADM79XG", "0.00 USD", "10.00 USD"),
+        ])
+        .unwrap();
+
+        assert!(sut.is_balanced());
+        assert_eq!(sut.totals().len(), 2);
+        assert_eq!(
+            sut.totals()[0].debit(),
+            "2000 JPY".parse::<Money>().unwrap()
+        );
+        assert_eq!(
+            sut.lines()[0].balance().unwrap(),
+            "1300 JPY".parse::<Money>().unwrap()
+        );
+        assert_eq!(
+            sut.lines()[1].balance().unwrap(),
+            "1000 JPY".parse::<Money>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_trial_balance_unbalanced() {
+        let sut = TrialBalance::new(vec![
+            line("funding:JPY", "1500 JPY", "0 JPY"),
+            line("wallet:01F8MECHZX3TBDSZ7XRADM79XE", "0 JPY", "1400 JPY"),
+        ])
+        .unwrap();
+
+        assert!(!sut.is_balanced());
+
+        // err
+        assert!(TrialBalanceLine::new(
+            "funding:JPY".parse().unwrap(),
+            "0 JPY".parse().unwrap(),
+            "0.00 USD".parse().unwrap(),
+        )
+        .is_err());
+    }
+}
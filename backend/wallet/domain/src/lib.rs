@@ -1,4 +1,8 @@
 pub mod aggregate;
 pub mod entity;
+pub mod error;
+pub mod event;
+pub mod ledger;
 pub mod repository;
+pub mod service;
 pub mod vo;
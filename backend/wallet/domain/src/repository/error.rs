@@ -0,0 +1,8 @@
+use crate::vo::Version;
+use thiserror::Error;
+
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum ConcurrencyError {
+    #[error("{id} was modified concurrently, expected version {expected}")]
+    VersionConflict { id: String, expected: Version },
+}
@@ -1,7 +1,7 @@
+use crate::error::{Error, Result};
 use crate::vo::Id;
-use anyhow::{Error, Result};
 
-pub trait IdRepository {
+pub trait IdRepository: Send + Sync {
     fn generate<T>(&self) -> Result<Id<T>, Error>;
 }
 
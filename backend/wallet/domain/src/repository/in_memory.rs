@@ -0,0 +1,427 @@
+use crate::entity::{Transaction, Wallet};
+use crate::error::{Error, Result};
+use crate::repository::*;
+use crate::vo::Id;
+use async_trait::async_trait;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+#[derive(Clone)]
+struct Stored {
+    wallet: Wallet,
+    deleted_at: Option<SystemTime>,
+}
+
+#[derive(Clone, Default)]
+struct Store {
+    wallets: HashMap<Id<Wallet>, Stored>,
+    transactions: Vec<Transaction>,
+}
+
+/// Keeps wallets in memory, for exercising use cases without a database.
+///
+/// A transaction works on a copy of what was committed when it began. Committing writes back
+/// the wallets it touched, so of two transactions touching the same wallet the last to commit
+/// wins; the version checks the repository makes catch that only within a transaction.
+#[derive(Clone, Default)]
+pub struct InMemoryUnitOfWork {
+    committed: Arc<Mutex<Store>>,
+}
+
+struct Changes {
+    store: Store,
+    touched: HashSet<Id<Wallet>>,
+    recorded: usize,
+}
+
+type SharedChanges = Arc<Mutex<Option<Changes>>>;
+
+pub struct InMemoryTx {
+    changes: SharedChanges,
+}
+
+#[derive(Clone)]
+pub struct InMemoryWalletRepository {
+    changes: SharedChanges,
+}
+
+impl InMemoryUnitOfWork {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stores `wallet` as committed, as if it had been created earlier.
+    pub fn insert(&self, wallet: Wallet) {
+        self.store(wallet, None);
+    }
+
+    /// Stores `wallet` as committed and deleted at `deleted_at`.
+    pub fn insert_deleted(&self, wallet: Wallet, deleted_at: SystemTime) {
+        self.store(wallet, Some(deleted_at));
+    }
+
+    /// Returns the transactions recorded by committed writes, oldest first.
+    pub fn transactions(&self) -> Vec<Transaction> {
+        self.committed.lock().unwrap().transactions.clone()
+    }
+
+    fn store(&self, wallet: Wallet, deleted_at: Option<SystemTime>) {
+        self.committed
+            .lock()
+            .unwrap()
+            .wallets
+            .insert(wallet.id().clone(), Stored::new(wallet, deleted_at));
+    }
+}
+
+#[async_trait]
+impl UnitOfWork for InMemoryUnitOfWork {
+    type Tx = InMemoryTx;
+    type Wallets = InMemoryWalletRepository;
+
+    async fn begin(&self) -> Result<InMemoryTx, Error> {
+        let store = self.committed.lock().unwrap().clone();
+        let recorded = store.transactions.len();
+
+        Ok(InMemoryTx {
+            changes: Arc::new(Mutex::new(Some(Changes {
+                store,
+                touched: HashSet::new(),
+                recorded,
+            }))),
+        })
+    }
+
+    fn wallets(&self, tx: &InMemoryTx) -> InMemoryWalletRepository {
+        InMemoryWalletRepository {
+            changes: tx.changes.clone(),
+        }
+    }
+
+    async fn commit(&self, tx: InMemoryTx) -> Result<(), Error> {
+        let mut changes = finish(&tx.changes)?;
+        let mut committed = self.committed.lock().unwrap();
+        for id in changes.touched {
+            match changes.store.wallets.remove(&id) {
+                Some(stored) => committed.wallets.insert(id, stored),
+                None => committed.wallets.remove(&id),
+            };
+        }
+        committed
+            .transactions
+            .extend(changes.store.transactions.drain(changes.recorded..));
+
+        Ok(())
+    }
+
+    async fn rollback(&self, tx: InMemoryTx) -> Result<(), Error> {
+        finish(&tx.changes).map(|_| ())
+    }
+}
+
+fn finish(changes: &SharedChanges) -> Result<Changes, Error> {
+    changes.lock().unwrap().take().ok_or_else(finished)
+}
+
+fn finished() -> Error {
+    Error::internal(anyhow::anyhow!("the transaction has already finished"))
+}
+
+impl Stored {
+    // what is stored is what a later read returns, so nothing waits to be persisted in it
+    fn new(mut wallet: Wallet, deleted_at: Option<SystemTime>) -> Self {
+        wallet.take_transactions();
+        wallet.take_journal_entries();
+        wallet.take_events();
+
+        Self { wallet, deleted_at }
+    }
+}
+
+impl Changes {
+    fn find(&self, id: &Id<Wallet>, deleted: bool) -> Result<&Stored, Error> {
+        match self.store.wallets.get(id) {
+            Some(stored) if stored.deleted_at.is_some() == deleted => Ok(stored),
+            _ if deleted => Err(Error::not_found("deleted wallet", id)),
+            _ => Err(Error::not_found("wallet", id)),
+        }
+    }
+
+    // writes `wallet` over the stored one it was read from, bumping its version like the
+    // database repositories do
+    fn write(
+        &mut self,
+        mut wallet: Wallet,
+        deleted: bool,
+        deleted_at: Option<SystemTime>,
+    ) -> Result<Wallet, Error> {
+        if self.find(wallet.id(), deleted)?.wallet.version() != wallet.version() {
+            return Err(ConcurrencyError::VersionConflict {
+                id: wallet.id().to_string(),
+                expected: *wallet.version(),
+            }
+            .into());
+        }
+
+        self.store.transactions.extend(wallet.take_transactions());
+        wallet.take_journal_entries();
+        wallet.set_version(wallet.version().next());
+        self.put(Stored::new(wallet.clone(), deleted_at));
+
+        Ok(wallet)
+    }
+
+    fn put(&mut self, stored: Stored) {
+        let id = stored.wallet.id().clone();
+        self.store.wallets.insert(id.clone(), stored);
+        self.touched.insert(id);
+    }
+}
+
+impl InMemoryWalletRepository {
+    fn with<T>(&self, f: impl FnOnce(&mut Changes) -> Result<T, Error>) -> Result<T, Error> {
+        match self.changes.lock().unwrap().as_mut() {
+            Some(changes) => f(changes),
+            None => Err(finished()),
+        }
+    }
+}
+
+#[async_trait]
+impl CreateRepository<Wallet> for InMemoryWalletRepository {
+    async fn create(&self, mut entity: Wallet) -> Result<(), Error> {
+        self.with(|changes| {
+            if changes.store.wallets.contains_key(entity.id()) {
+                return Err(Error::internal(anyhow::anyhow!(
+                    "wallet {} already exists",
+                    entity.id()
+                )));
+            }
+
+            changes
+                .store
+                .transactions
+                .extend(entity.take_transactions());
+            changes.put(Stored::new(entity, None));
+
+            Ok(())
+        })
+    }
+}
+
+#[async_trait]
+impl GetRepository<Wallet> for InMemoryWalletRepository {
+    async fn get(&self, id: Id<Wallet>) -> Result<Wallet, Error> {
+        self.with(|changes| Ok(changes.find(&id, false)?.wallet.clone()))
+    }
+}
+
+#[async_trait]
+impl UpdateRepository<Wallet> for InMemoryWalletRepository {
+    async fn update(&self, entity: Wallet) -> Result<Wallet, Error> {
+        self.with(|changes| changes.write(entity, false, None))
+    }
+}
+
+#[async_trait]
+impl TransferRepository<Wallet> for InMemoryWalletRepository {
+    async fn transfer<F>(
+        &self,
+        from: Id<Wallet>,
+        to: Id<Wallet>,
+        f: F,
+    ) -> Result<(Wallet, Wallet), Error>
+    where
+        F: FnOnce(&mut Wallet, &mut Wallet) -> Result<(), Error> + Send + 'static,
+    {
+        self.with(|changes| {
+            let mut from = changes.find(&from, false)?.wallet.clone();
+            let mut to = changes.find(&to, false)?.wallet.clone();
+
+            f(&mut from, &mut to)?;
+
+            let from = changes.write(from, false, None)?;
+            let to = changes.write(to, false, None)?;
+
+            Ok((from, to))
+        })
+    }
+}
+
+#[async_trait]
+impl DeleteRepository<Wallet> for InMemoryWalletRepository {
+    async fn delete(&self, entity: Wallet) -> Result<(), Error> {
+        self.with(|changes| {
+            changes.write(entity, false, Some(SystemTime::now()))?;
+
+            Ok(())
+        })
+    }
+}
+
+#[async_trait]
+impl UndeleteRepository<Wallet> for InMemoryWalletRepository {
+    async fn get_deleted(&self, id: Id<Wallet>) -> Result<(Wallet, SystemTime), Error> {
+        self.with(|changes| {
+            let stored = changes.find(&id, true)?;
+
+            Ok((stored.wallet.clone(), stored.deleted_at.unwrap()))
+        })
+    }
+
+    async fn undelete(&self, entity: Wallet) -> Result<Wallet, Error> {
+        self.with(|changes| changes.write(entity, true, None))
+    }
+}
+
+#[async_trait]
+impl PurgeRepository<Wallet> for InMemoryWalletRepository {
+    async fn purge(&self, deleted_before: SystemTime) -> Result<usize, Error> {
+        self.with(|changes| {
+            let purged = changes
+                .store
+                .wallets
+                .iter()
+                .filter(|(_, stored)| matches!(stored.deleted_at, Some(at) if at < deleted_before))
+                .map(|(id, _)| id.clone())
+                .collect::<Vec<_>>();
+            for id in &purged {
+                changes.store.wallets.remove(id);
+                changes.touched.insert(id.clone());
+            }
+
+            Ok(purged.len())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vo::{Currency, Memo, Money, Owner, Version};
+    use std::time::Duration;
+
+    fn new_wallet(id: &str) -> Wallet {
+        Wallet::create(
+            id.parse::<Id<Wallet>>().unwrap(),
+            "alice".parse::<Owner>().unwrap(),
+            Currency::JPY,
+        )
+    }
+
+    fn deposit(wallet: &mut Wallet, amount: &str) {
+        wallet
+            .deposit(
+                "01F8MECHZX3TBDSZ7XRADM79XA".parse::<Id<_>>().unwrap(),
+                amount.parse::<Money>().unwrap(),
+                Memo::default(),
+            )
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_unit_of_work_commit() {
+        let sut = InMemoryUnitOfWork::new();
+        let wallet = new_wallet("01F8MECHZX3TBDSZ7XRADM79XE");
+
+        let tx = sut.begin().await.unwrap();
+        let wallets = sut.wallets(&tx);
+        wallets.create(wallet.clone()).await.unwrap();
+        let mut stored = wallets.get(wallet.id().clone()).await.unwrap();
+        assert!(stored.events().is_empty());
+        deposit(&mut stored, "1000 JPY");
+        let updated = wallets.update(stored).await.unwrap();
+        assert_eq!(updated.version(), &Version::new(2));
+        assert!(updated.transactions().is_empty());
+        assert_eq!(updated.events().len(), 1);
+
+        // not seen outside the transaction until it is committed
+        let other = sut.begin().await.unwrap();
+        assert!(sut.wallets(&other).get(wallet.id().clone()).await.is_err());
+        assert!(sut.transactions().is_empty());
+
+        sut.commit(tx).await.unwrap();
+        let tx = sut.begin().await.unwrap();
+        let stored = sut.wallets(&tx).get(wallet.id().clone()).await.unwrap();
+        assert_eq!(stored.balance(), &"1000 JPY".parse::<Money>().unwrap());
+        assert!(stored.events().is_empty());
+        assert_eq!(sut.transactions().len(), 1);
+
+        // err: a repository outlives its transaction
+        assert!(wallets.get(wallet.id().clone()).await.is_err());
+        // err: stale version
+        assert!(sut.wallets(&tx).update(wallet).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_unit_of_work_rollback() {
+        let sut = InMemoryUnitOfWork::new();
+        let wallet = new_wallet("01F8MECHZX3TBDSZ7XRADM79XE");
+        sut.insert(wallet.clone());
+
+        let tx = sut.begin().await.unwrap();
+        sut.wallets(&tx).delete(wallet.clone()).await.unwrap();
+        sut.rollback(tx).await.unwrap();
+
+        // dropped without committing
+        let tx = sut.begin().await.unwrap();
+        sut.wallets(&tx).delete(wallet.clone()).await.unwrap();
+        drop(tx);
+
+        let tx = sut.begin().await.unwrap();
+        assert_eq!(
+            sut.wallets(&tx).get(wallet.id().clone()).await.unwrap(),
+            wallet
+        );
+
+        // finish commits a success and rolls back a failure
+        let wallets = sut.wallets(&tx);
+        let result = wallets.delete(wallet.clone()).await;
+        sut.finish(tx, result).await.unwrap();
+        let tx = sut.begin().await.unwrap();
+        let (deleted, _) = sut
+            .wallets(&tx)
+            .get_deleted(wallet.id().clone())
+            .await
+            .unwrap();
+        let result = sut
+            .wallets(&tx)
+            .undelete(deleted)
+            .await
+            .and_then(|_| Err::<(), _>(Error::failed_precondition("TEST", "changed my mind")));
+        assert!(sut.finish(tx, result).await.is_err());
+
+        let tx = sut.begin().await.unwrap();
+        assert!(sut
+            .wallets(&tx)
+            .get_deleted(wallet.id().clone())
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_unit_of_work_purge() {
+        let sut = InMemoryUnitOfWork::new();
+        let day = Duration::from_secs(24 * 60 * 60);
+        let old = new_wallet("01F8MECHZX3TBDSZ7XRADM79XE");
+        let recent = new_wallet("01F8MECHZX3TBDSZ7XRADM79XF");
+        sut.insert_deleted(old.clone(), SystemTime::now() - 2 * day);
+        sut.insert_deleted(recent.clone(), SystemTime::now());
+
+        let tx = sut.begin().await.unwrap();
+        assert_eq!(
+            sut.wallets(&tx)
+                .purge(SystemTime::now() - day)
+                .await
+                .unwrap(),
+            1
+        );
+        sut.commit(tx).await.unwrap();
+
+        let tx = sut.begin().await.unwrap();
+        let wallets = sut.wallets(&tx);
+        assert!(wallets.get_deleted(old.id().clone()).await.is_err());
+        assert!(wallets.get_deleted(recent.id().clone()).await.is_ok());
+    }
+}
@@ -1,5 +1,13 @@
+mod error;
 mod id;
+#[cfg(any(test, feature = "test-support"))]
+mod in_memory;
 mod resource;
+mod unit_of_work;
 
+pub use self::error::*;
 pub use self::id::*;
+#[cfg(any(test, feature = "test-support"))]
+pub use self::in_memory::*;
 pub use self::resource::*;
+pub use self::unit_of_work::*;
@@ -1,26 +1,70 @@
 use crate::aggregate::AggregateRoot;
+use crate::error::{Error, Result};
 use crate::vo::Id;
-use anyhow::{Error, Result};
+use async_trait::async_trait;
+use std::time::SystemTime;
 
-pub trait CreateRepository<T>
+#[async_trait]
+pub trait CreateRepository<T>: Send + Sync
 where
-    T: AggregateRoot,
+    T: AggregateRoot + Send + 'static,
 {
-    fn create(&self, entity: T) -> Result<(), Error>;
+    async fn create(&self, entity: T) -> Result<(), Error>;
 }
 
-pub trait GetRepository<T>
+#[async_trait]
+pub trait GetRepository<T>: Send + Sync
 where
-    T: AggregateRoot,
+    T: AggregateRoot + Send + 'static,
 {
-    fn get(&self, id: Id<T>) -> Result<T, Error>;
+    async fn get(&self, id: Id<T>) -> Result<T, Error>;
 }
 
-pub trait DeleteRepository<T>
+#[async_trait]
+pub trait UpdateRepository<T>: Send + Sync
 where
-    T: AggregateRoot,
+    T: AggregateRoot + Send + 'static,
 {
-    fn delete(&self, entity: T) -> Result<(), Error>;
+    /// Stores `entity` and returns it as persisted, e.g. with its version bumped.
+    async fn update(&self, entity: T) -> Result<T, Error>;
+}
+
+#[async_trait]
+pub trait TransferRepository<T>: Send + Sync
+where
+    T: AggregateRoot + Send + 'static,
+{
+    async fn transfer<F>(&self, from: Id<T>, to: Id<T>, f: F) -> Result<(T, T), Error>
+    where
+        F: FnOnce(&mut T, &mut T) -> Result<(), Error> + Send + 'static;
+}
+
+#[async_trait]
+pub trait DeleteRepository<T>: Send + Sync
+where
+    T: AggregateRoot + Send + 'static,
+{
+    async fn delete(&self, entity: T) -> Result<(), Error>;
+}
+
+#[async_trait]
+pub trait UndeleteRepository<T>: Send + Sync
+where
+    T: AggregateRoot + Send + 'static,
+{
+    /// Returns a soft-deleted aggregate together with the time it was deleted.
+    async fn get_deleted(&self, id: Id<T>) -> Result<(T, SystemTime), Error>;
+    async fn undelete(&self, entity: T) -> Result<T, Error>;
+}
+
+#[async_trait]
+pub trait PurgeRepository<T>: Send + Sync
+where
+    T: AggregateRoot + Send + 'static,
+{
+    /// Permanently removes aggregates soft-deleted before `deleted_before` and returns how many
+    /// were removed.
+    async fn purge(&self, deleted_before: SystemTime) -> Result<usize, Error>;
 }
 
 #[cfg(test)]
@@ -28,7 +72,6 @@ mod tests {
     use super::*;
     use crate::entity::Entity;
     use crate::vo::Id;
-    use anyhow::bail;
     use std::collections::HashMap;
     use std::hash::Hash;
     use std::sync::Arc;
@@ -38,7 +81,13 @@ mod tests {
     struct DummyEntity {
         id: Id<DummyEntity>,
     }
-    impl AggregateRoot for DummyEntity {}
+    impl AggregateRoot for DummyEntity {
+        type Event = ();
+
+        fn take_events(&mut self) -> Vec<Self::Event> {
+            vec![]
+        }
+    }
     impl Entity for DummyEntity {
         type Id = Id<DummyEntity>;
 
@@ -47,83 +96,313 @@ mod tests {
         }
     }
 
+    type DeletedStore = Arc<Mutex<HashMap<Id<DummyEntity>, (DummyEntity, SystemTime)>>>;
+
     struct DummyEntityRepository {
         store: Arc<Mutex<HashMap<Id<DummyEntity>, DummyEntity>>>,
+        deleted: DeletedStore,
     }
     impl DummyEntityRepository {
         fn new() -> Self {
             let m = HashMap::new();
             Self {
                 store: Arc::new(Mutex::new(m)),
+                deleted: Arc::new(Mutex::new(HashMap::new())),
             }
         }
     }
 
+    #[async_trait]
     impl CreateRepository<DummyEntity> for DummyEntityRepository {
-        fn create(&self, entity: DummyEntity) -> Result<(), Error> {
+        async fn create(&self, entity: DummyEntity) -> Result<(), Error> {
             let mut m = self.store.lock().unwrap();
             m.insert(entity.id.clone(), entity);
             Ok(())
         }
     }
 
+    #[async_trait]
     impl GetRepository<DummyEntity> for DummyEntityRepository {
-        fn get(&self, id: Id<DummyEntity>) -> Result<DummyEntity, Error> {
+        async fn get(&self, id: Id<DummyEntity>) -> Result<DummyEntity, Error> {
             let m = self.store.lock().unwrap();
             match m.get(&id) {
                 Some(aggregate_root) => Ok(aggregate_root.clone()),
-                None => bail!("not found entity"),
+                None => Err(Error::not_found("entity", id)),
             }
         }
     }
 
+    #[async_trait]
+    impl UpdateRepository<DummyEntity> for DummyEntityRepository {
+        async fn update(&self, entity: DummyEntity) -> Result<DummyEntity, Error> {
+            let mut m = self.store.lock().unwrap();
+            match m.get_mut(&entity.id) {
+                Some(aggregate_root) => {
+                    *aggregate_root = entity.clone();
+                    Ok(entity)
+                }
+                None => Err(Error::not_found("entity", entity.id)),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl TransferRepository<DummyEntity> for DummyEntityRepository {
+        async fn transfer<F>(
+            &self,
+            from: Id<DummyEntity>,
+            to: Id<DummyEntity>,
+            f: F,
+        ) -> Result<(DummyEntity, DummyEntity), Error>
+        where
+            F: FnOnce(&mut DummyEntity, &mut DummyEntity) -> Result<(), Error> + Send + 'static,
+        {
+            let mut m = self.store.lock().unwrap();
+            let (mut from, mut to) = match (m.get(&from), m.get(&to)) {
+                (Some(from), Some(to)) => (from.clone(), to.clone()),
+                _ => return Err(Error::not_found("entity", from)),
+            };
+
+            f(&mut from, &mut to)?;
+
+            m.insert(from.id.clone(), from.clone());
+            m.insert(to.id.clone(), to.clone());
+            Ok((from, to))
+        }
+    }
+
+    #[async_trait]
     impl DeleteRepository<DummyEntity> for DummyEntityRepository {
-        fn delete(&self, entity: DummyEntity) -> Result<(), Error> {
+        async fn delete(&self, entity: DummyEntity) -> Result<(), Error> {
             let mut m = self.store.lock().unwrap();
             match m.remove(&entity.id) {
-                Some(_) => Ok(()),
-                None => bail!("not found entity"),
+                Some(entity) => {
+                    let mut d = self.deleted.lock().unwrap();
+                    d.insert(entity.id.clone(), (entity, SystemTime::now()));
+                    Ok(())
+                }
+                None => Err(Error::not_found("entity", entity.id)),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl UndeleteRepository<DummyEntity> for DummyEntityRepository {
+        async fn get_deleted(
+            &self,
+            id: Id<DummyEntity>,
+        ) -> Result<(DummyEntity, SystemTime), Error> {
+            let d = self.deleted.lock().unwrap();
+            match d.get(&id) {
+                Some(deleted) => Ok(deleted.clone()),
+                None => Err(Error::not_found("entity", id)),
+            }
+        }
+
+        async fn undelete(&self, entity: DummyEntity) -> Result<DummyEntity, Error> {
+            let mut d = self.deleted.lock().unwrap();
+            match d.remove(&entity.id) {
+                Some((entity, _)) => {
+                    let mut m = self.store.lock().unwrap();
+                    m.insert(entity.id.clone(), entity.clone());
+                    Ok(entity)
+                }
+                None => Err(Error::not_found("entity", entity.id)),
             }
         }
     }
 
-    #[test]
-    fn test_create_repository_for_entity() {
+    #[async_trait]
+    impl PurgeRepository<DummyEntity> for DummyEntityRepository {
+        async fn purge(&self, deleted_before: SystemTime) -> Result<usize, Error> {
+            let mut d = self.deleted.lock().unwrap();
+            let before = d.len();
+            d.retain(|_, (_, deleted_at)| *deleted_at >= deleted_before);
+            Ok(before - d.len())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_repository_for_entity() {
         let entity = DummyEntity {
-            id: "hogehoge".parse::<Id<DummyEntity>>().unwrap(),
+            id: "01F8MECHZX3TBDSZ7XRADM79XE"
+                .parse::<Id<DummyEntity>>()
+                .unwrap(),
         };
 
         let sut = DummyEntityRepository::new();
 
-        assert!(sut.create(entity).is_ok());
+        assert!(sut.create(entity).await.is_ok());
     }
 
-    #[test]
-    fn test_get_repository_for_entity() {
+    #[tokio::test]
+    async fn test_get_repository_for_entity() {
         let entity = DummyEntity {
-            id: "hogehoge".parse::<Id<DummyEntity>>().unwrap(),
+            id: "01F8MECHZX3TBDSZ7XRADM79XE"
+                .parse::<Id<DummyEntity>>()
+                .unwrap(),
         };
 
         let sut = DummyEntityRepository::new();
-        sut.create(entity).unwrap();
+        sut.create(entity).await.unwrap();
 
         assert!(sut
-            .get("hogehoge".parse::<Id<DummyEntity>>().unwrap())
+            .get(
+                "01F8MECHZX3TBDSZ7XRADM79XE"
+                    .parse::<Id<DummyEntity>>()
+                    .unwrap()
+            )
+            .await
             .is_ok());
     }
 
-    #[test]
-    fn test_delete_repository_for_entity() {
+    #[tokio::test]
+    async fn test_update_repository_for_entity() {
         let entity = DummyEntity {
-            id: "hogehoge".parse::<Id<DummyEntity>>().unwrap(),
+            id: "01F8MECHZX3TBDSZ7XRADM79XE"
+                .parse::<Id<DummyEntity>>()
+                .unwrap(),
         };
 
         let sut = DummyEntityRepository::new();
-        sut.create(entity).unwrap();
+        sut.create(entity).await.unwrap();
         let target = sut
-            .get("hogehoge".parse::<Id<DummyEntity>>().unwrap())
+            .get(
+                "01F8MECHZX3TBDSZ7XRADM79XE"
+                    .parse::<Id<DummyEntity>>()
+                    .unwrap(),
+            )
+            .await
             .unwrap();
 
-        assert!(sut.delete(target).is_ok());
+        assert!(sut.update(target).await.is_ok());
+        assert!(sut
+            .update(DummyEntity {
+                id: "01F8MECHZX3TBDSZ7XRADM79XF"
+                    .parse::<Id<DummyEntity>>()
+                    .unwrap(),
+            })
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_transfer_repository_for_entity() {
+        let sut = DummyEntityRepository::new();
+        sut.create(DummyEntity {
+            id: "01F8MECHZX3TBDSZ7XRADM79XE"
+                .parse::<Id<DummyEntity>>()
+                .unwrap(),
+        })
+        .await
+        .unwrap();
+        sut.create(DummyEntity {
+            id: "01F8MECHZX3TBDSZ7XRADM79XF"
+                .parse::<Id<DummyEntity>>()
+                .unwrap(),
+        })
+        .await
+        .unwrap();
+
+        assert!(sut
+            .transfer(
+                "01F8MECHZX3TBDSZ7XRADM79XE"
+                    .parse::<Id<DummyEntity>>()
+                    .unwrap(),
+                "01F8MECHZX3TBDSZ7XRADM79XF"
+                    .parse::<Id<DummyEntity>>()
+                    .unwrap(),
+                |_, _| Ok(()),
+            )
+            .await
+            .is_ok());
+        assert!(sut
+            .transfer(
+                "01F8MECHZX3TBDSZ7XRADM79XE"
+                    .parse::<Id<DummyEntity>>()
+                    .unwrap(),
+                "01F8MECHZX3TBDSZ7XRADM79XF"
+                    .parse::<Id<DummyEntity>>()
+                    .unwrap(),
+                |_, _| Err(Error::invalid_argument("amount", "error")),
+            )
+            .await
+            .is_err());
+        assert!(sut
+            .transfer(
+                "01F8MECHZX3TBDSZ7XRADM79XE"
+                    .parse::<Id<DummyEntity>>()
+                    .unwrap(),
+                "01F8MECHZX3TBDSZ7XRADM79XG"
+                    .parse::<Id<DummyEntity>>()
+                    .unwrap(),
+                |_, _| Ok(()),
+            )
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_delete_repository_for_entity() {
+        let entity = DummyEntity {
+            id: "01F8MECHZX3TBDSZ7XRADM79XE"
+                .parse::<Id<DummyEntity>>()
+                .unwrap(),
+        };
+
+        let sut = DummyEntityRepository::new();
+        sut.create(entity).await.unwrap();
+        let target = sut
+            .get(
+                "01F8MECHZX3TBDSZ7XRADM79XE"
+                    .parse::<Id<DummyEntity>>()
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert!(sut.delete(target).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_undelete_repository_for_entity() {
+        let entity = DummyEntity {
+            id: "01F8MECHZX3TBDSZ7XRADM79XE"
+                .parse::<Id<DummyEntity>>()
+                .unwrap(),
+        };
+
+        let sut = DummyEntityRepository::new();
+        sut.create(entity.clone()).await.unwrap();
+        sut.delete(entity.clone()).await.unwrap();
+
+        assert!(sut.get(entity.id.clone()).await.is_err());
+
+        let (target, _) = sut.get_deleted(entity.id.clone()).await.unwrap();
+        assert!(sut.undelete(target).await.is_ok());
+        assert!(sut.get(entity.id.clone()).await.is_ok());
+        assert!(sut.get_deleted(entity.id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_purge_repository_for_entity() {
+        let entity = DummyEntity {
+            id: "01F8MECHZX3TBDSZ7XRADM79XE"
+                .parse::<Id<DummyEntity>>()
+                .unwrap(),
+        };
+
+        let sut = DummyEntityRepository::new();
+        sut.create(entity.clone()).await.unwrap();
+        sut.delete(entity.clone()).await.unwrap();
+
+        assert_eq!(sut.purge(SystemTime::UNIX_EPOCH).await.unwrap(), 0);
+        assert_eq!(
+            sut.purge(SystemTime::now() + std::time::Duration::from_secs(1))
+                .await
+                .unwrap(),
+            1
+        );
+        assert!(sut.get_deleted(entity.id).await.is_err());
     }
 }
@@ -0,0 +1,53 @@
+use crate::entity::Wallet;
+use crate::error::{Error, Result};
+use crate::repository::{
+    CreateRepository, DeleteRepository, GetRepository, PurgeRepository, TransferRepository,
+    UndeleteRepository, UpdateRepository,
+};
+use async_trait::async_trait;
+
+/// Makes several repository calls one change that is saved as a whole or not at all.
+///
+/// `begin` starts a transaction and `wallets` hands out a repository taking part in it. What
+/// the repository writes is seen by nobody else until `commit`; `rollback`, or dropping the
+/// transaction without committing it, throws it away.
+#[async_trait]
+pub trait UnitOfWork: Send + Sync {
+    type Tx: Send + Sync + 'static;
+    type Wallets: CreateRepository<Wallet>
+        + GetRepository<Wallet>
+        + UpdateRepository<Wallet>
+        + TransferRepository<Wallet>
+        + DeleteRepository<Wallet>
+        + UndeleteRepository<Wallet>
+        + PurgeRepository<Wallet>;
+
+    async fn begin(&self) -> Result<Self::Tx, Error>;
+
+    fn wallets(&self, tx: &Self::Tx) -> Self::Wallets;
+
+    async fn commit(&self, tx: Self::Tx) -> Result<(), Error>;
+
+    async fn rollback(&self, tx: Self::Tx) -> Result<(), Error>;
+
+    /// Commits `tx` if `result` is a success and rolls it back otherwise, then hands `result`
+    /// back, or the error that kept it from being committed.
+    async fn finish<T>(&self, tx: Self::Tx, result: Result<T, Error>) -> Result<T, Error>
+    where
+        T: Send + 'static,
+    {
+        match result {
+            Ok(value) => {
+                self.commit(tx).await?;
+                Ok(value)
+            }
+            Err(e) => {
+                // what went wrong in the transaction matters more than failing to roll it back
+                if let Err(rollback) = self.rollback(tx).await {
+                    tracing::error!(error = %e, rollback = %rollback, "failed to roll back");
+                }
+                Err(e)
+            }
+        }
+    }
+}
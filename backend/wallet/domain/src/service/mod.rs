@@ -0,0 +1,3 @@
+mod transfer;
+
+pub use self::transfer::*;
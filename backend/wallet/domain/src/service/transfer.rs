@@ -0,0 +1,131 @@
+use crate::entity::{Transaction, Wallet};
+use crate::error::{Error, Result};
+use crate::vo::{Id, Memo, Money};
+
+#[derive(Default, Clone, Debug)]
+pub struct TransferService {}
+
+impl TransferService {
+    /// Moves `amount` from `from` to `to`, recording it as transaction `withdrawn` of the one and
+    /// `deposited` of the other. Neither wallet changes unless both can.
+    pub fn transfer(
+        &self,
+        from: &mut Wallet,
+        to: &mut Wallet,
+        amount: Money,
+        withdrawn: Id<Transaction>,
+        deposited: Id<Transaction>,
+        memo: Memo,
+    ) -> Result<(), Error> {
+        if from.id() == to.id() {
+            return Err(Error::invalid_argument(
+                "to_id",
+                "cannot transfer to the same wallet",
+            ));
+        }
+        if from.balance().currency() != to.balance().currency() {
+            return Err(Error::failed_precondition(
+                "CURRENCY_MISMATCH",
+                "cannot transfer between wallets of different currencies",
+            ));
+        }
+
+        let mut sender = from.clone();
+        let mut receiver = to.clone();
+        sender.transfer_out(withdrawn, to.id(), amount, memo.clone())?;
+        receiver.transfer_in(deposited, from.id(), amount, memo)?;
+
+        *from = sender;
+        *to = receiver;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::WalletBuilder;
+    use crate::event::WalletEvent;
+    use crate::ledger::{Account, Posting};
+    use crate::vo::{Owner, TransactionKind};
+
+    fn new_wallet(id: &str, balance: &str) -> Wallet {
+        WalletBuilder::default()
+            .id(id.parse::<Id<Wallet>>().unwrap())
+            .owner("alice".parse::<Owner>().unwrap())
+            .balance(balance.parse::<Money>().unwrap())
+            .build()
+            .unwrap()
+    }
+
+    fn transfer(from: &mut Wallet, to: &mut Wallet, amount: &str) -> Result<(), Error> {
+        TransferService::default().transfer(
+            from,
+            to,
+            amount.parse::<Money>().unwrap(),
+            "01F8MECHZX3TBDSZ7XRADM79XA".parse().unwrap(),
+            "01F8MECHZX3TBDSZ7XRADM79XB".parse().unwrap(),
+            "rent".parse::<Memo>().unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_transfer() {
+        let mut from = new_wallet("01F8MECHZX3TBDSZ7XRADM79XE", "1000 JPY");
+        let mut to = new_wallet("01F8MECHZX3TBDSZ7XRADM79XF", "0 JPY");
+        let amount = "300 JPY".parse::<Money>().unwrap();
+
+        assert!(transfer(&mut from, &mut to, "300 JPY").is_ok());
+        assert_eq!(from.balance(), &"700 JPY".parse::<Money>().unwrap());
+        assert_eq!(to.balance(), &"300 JPY".parse::<Money>().unwrap());
+
+        assert_eq!(from.transactions()[0].kind(), &TransactionKind::TransferOut);
+        assert_eq!(
+            from.transactions()[0].counterparty(),
+            &Some(to.id().clone())
+        );
+        assert_eq!(to.transactions()[0].kind(), &TransactionKind::TransferIn);
+        assert_eq!(
+            to.transactions()[0].counterparty(),
+            &Some(from.id().clone())
+        );
+        assert_eq!(to.events(), &vec![WalletEvent::from(&to.transactions()[0])]);
+
+        // one entry moves the money between both wallet accounts
+        assert_eq!(from.journal_entries().len(), 1);
+        assert!(to.journal_entries().is_empty());
+        assert_eq!(
+            from.journal_entries()[0].postings(),
+            &vec![
+                Posting::debit(Account::Wallet(from.id().clone()), amount),
+                Posting::credit(Account::Wallet(to.id().clone()), amount),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_transfer_err() {
+        let mut from = new_wallet("01F8MECHZX3TBDSZ7XRADM79XE", "1000 JPY");
+        let mut to = new_wallet("01F8MECHZX3TBDSZ7XRADM79XF", "0 JPY");
+        let mut same = from.clone();
+        let mut usd = new_wallet("01F8MECHZX3TBDSZ7XRADM79XG", "0 USD");
+
+        assert!(transfer(&mut from, &mut to, "1001 JPY").is_err());
+        assert!(transfer(&mut from, &mut to, "0 JPY").is_err());
+        assert!(transfer(&mut from, &mut same, "100 JPY").is_err());
+        assert_eq!(
+            transfer(&mut from, &mut usd, "100 JPY")
+                .unwrap_err()
+                .reason(),
+            "CURRENCY_MISMATCH"
+        );
+
+        // nothing is touched on failure
+        assert_eq!(from.balance(), &"1000 JPY".parse::<Money>().unwrap());
+        assert_eq!(to.balance(), &"0 JPY".parse::<Money>().unwrap());
+        assert_eq!(same.balance(), &"1000 JPY".parse::<Money>().unwrap());
+        assert!(from.transactions().is_empty());
+        assert!(to.journal_entries().is_empty());
+    }
+}
@@ -0,0 +1,58 @@
+use crate::vo::ValueObject;
+use parse_display::{Display, FromStr};
+
+/// ISO 4217 currency code.
+#[derive(Display, FromStr, Default, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Currency {
+    AUD,
+    BHD,
+    CAD,
+    CHF,
+    CNY,
+    EUR,
+    GBP,
+    #[default]
+    JPY,
+    KRW,
+    KWD,
+    USD,
+}
+
+impl ValueObject for Currency {}
+
+impl Currency {
+    /// Number of digits after the decimal separator (ISO 4217 minor unit).
+    pub fn minor_unit(&self) -> u32 {
+        match self {
+            Currency::JPY | Currency::KRW => 0,
+            Currency::BHD | Currency::KWD => 3,
+            _ => 2,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_currency_from_str() {
+        assert_eq!("JPY".parse::<Currency>().unwrap(), Currency::JPY);
+        assert_eq!("USD".parse::<Currency>().unwrap(), Currency::USD);
+        assert!("jpy".parse::<Currency>().is_err());
+        assert!("XXX".parse::<Currency>().is_err());
+    }
+
+    #[test]
+    fn test_currency_to_string() {
+        assert_eq!(Currency::JPY.to_string(), "JPY".to_string());
+        assert_eq!(Currency::USD.to_string(), "USD".to_string());
+    }
+
+    #[test]
+    fn test_currency_minor_unit() {
+        assert_eq!(Currency::JPY.minor_unit(), 0);
+        assert_eq!(Currency::USD.minor_unit(), 2);
+        assert_eq!(Currency::KWD.minor_unit(), 3);
+    }
+}
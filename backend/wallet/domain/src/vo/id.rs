@@ -1,10 +1,27 @@
 use crate::vo::Identifier;
 use crate::vo::ValueObject;
-use anyhow::Error;
+use std::fmt;
 use std::hash::{Hash, Hasher};
 use std::marker::PhantomData;
 use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+const ULID_LENGTH: usize = 26;
+const TIMESTAMP_LENGTH: usize = 10;
+const CROCKFORD_BASE32: &str = "0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum IdError {
+    #[error("id must be {} characters, got {0}", ULID_LENGTH)]
+    InvalidLength(usize),
+    #[error("id contains a character outside Crockford's base32: {0:?}")]
+    InvalidCharacter(char),
+    #[error("id exceeds the maximum ULID value")]
+    Overflow,
+}
 
+/// A ULID: 26 Crockford base32 characters, the first 10 of which encode the creation time.
 #[derive(Debug, Default, Clone, Eq)]
 pub struct Id<E>(String, PhantomData<E>);
 
@@ -12,17 +29,51 @@ impl<E> ValueObject for Id<E> {}
 
 impl<E> Identifier for Id<E> {}
 
+impl<E> Id<E> {
+    /// Milliseconds since the Unix epoch at which the id was generated.
+    pub fn timestamp_ms(&self) -> u64 {
+        self.0
+            .chars()
+            .take(TIMESTAMP_LENGTH)
+            .filter_map(|c| CROCKFORD_BASE32.find(c))
+            .fold(0, |acc, v| (acc << 5) | v as u64)
+    }
+
+    pub fn created_at(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(self.timestamp_ms())
+    }
+
+    /// Reuses the ULID for a record of another type that is derived one-to-one from this one.
+    pub(crate) fn cast<F>(&self) -> Id<F> {
+        Id(self.0.clone(), PhantomData)
+    }
+}
+
 impl<E> FromStr for Id<E> {
-    type Err = Error;
+    type Err = IdError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Self(s.to_string(), PhantomData))
+        let s = s.to_ascii_uppercase();
+
+        let length = s.chars().count();
+        if length != ULID_LENGTH {
+            return Err(IdError::InvalidLength(length));
+        }
+        if let Some(c) = s.chars().find(|c| !CROCKFORD_BASE32.contains(*c)) {
+            return Err(IdError::InvalidCharacter(c));
+        }
+        // 26 characters carry 130 bits, so the leading one may only use the low 3 bits
+        if s.as_bytes()[0] > b'7' {
+            return Err(IdError::Overflow);
+        }
+
+        Ok(Self(s, PhantomData))
     }
 }
 
-impl<T> ToString for Id<T> {
-    fn to_string(&self) -> String {
-        self.0.clone()
+impl<T> fmt::Display for Id<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
     }
 }
 
@@ -61,6 +112,32 @@ mod tests {
         assert!("01F8MECHZX3TBDSZ7XRADM79XE"
             .parse::<Id::<EnumEntity>>()
             .is_ok());
+        assert!("7ZZZZZZZZZZZZZZZZZZZZZZZZZ".parse::<Id::<Entity>>().is_ok());
+    }
+
+    #[test]
+    fn test_id_err() {
+        assert_eq!(
+            "hogehoge".parse::<Id::<Entity>>(),
+            Err(IdError::InvalidLength(8))
+        );
+        assert_eq!("".parse::<Id::<Entity>>(), Err(IdError::InvalidLength(0)));
+        assert_eq!(
+            "01F8MECHZX3TBDSZ7XRADM79XEE".parse::<Id::<Entity>>(),
+            Err(IdError::InvalidLength(27))
+        );
+        assert_eq!(
+            "01F8MECHZX3TBDSZ7XRADM79XU".parse::<Id::<Entity>>(),
+            Err(IdError::InvalidCharacter('U'))
+        );
+        assert_eq!(
+            "01F8MECHZX3TBDSZ7XRADM79X-".parse::<Id::<Entity>>(),
+            Err(IdError::InvalidCharacter('-'))
+        );
+        assert_eq!(
+            "80000000000000000000000000".parse::<Id::<Entity>>(),
+            Err(IdError::Overflow)
+        );
     }
 
     #[test]
@@ -72,6 +149,24 @@ mod tests {
                 .to_string(),
             "01F8MECHZX3TBDSZ7XRADM79XE".to_string()
         );
+        assert_eq!(
+            "01f8mechzx3tbdsz7xradm79xe"
+                .parse::<Id::<Entity>>()
+                .unwrap()
+                .to_string(),
+            "01F8MECHZX3TBDSZ7XRADM79XE".to_string()
+        );
+    }
+
+    #[test]
+    fn test_id_timestamp() {
+        let id = "01F8MECHZX3TBDSZ7XRADM79XE".parse::<Id<Entity>>().unwrap();
+
+        assert_eq!(id.timestamp_ms(), 1624183818237);
+        assert_eq!(
+            id.created_at(),
+            UNIX_EPOCH + Duration::from_millis(1624183818237)
+        );
     }
 
     #[test]
@@ -93,6 +188,6 @@ mod tests {
 
         m.insert(id.clone(), entity);
 
-        assert!(m.get(&id).is_some());
+        assert!(m.contains_key(&id));
     }
 }
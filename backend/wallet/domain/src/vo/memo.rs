@@ -0,0 +1,55 @@
+use crate::vo::ValueObject;
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+const MEMO_MAX_LENGTH: usize = 255;
+
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum MemoError {
+    #[error("memo must be at most {} characters, got {0}", MEMO_MAX_LENGTH)]
+    TooLong(usize),
+}
+
+/// Free-form note a client attaches to a balance change.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct Memo(String);
+
+impl ValueObject for Memo {}
+
+impl FromStr for Memo {
+    type Err = MemoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let length = s.chars().count();
+        if length > MEMO_MAX_LENGTH {
+            return Err(MemoError::TooLong(length));
+        }
+
+        Ok(Self(s.to_string()))
+    }
+}
+
+impl fmt::Display for Memo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memo() {
+        assert!("".parse::<Memo>().is_ok());
+        assert!("rent for october".parse::<Memo>().is_ok());
+        assert!("あ".repeat(255).parse::<Memo>().is_ok());
+
+        // err
+        assert_eq!(
+            "a".repeat(256).parse::<Memo>(),
+            Err(MemoError::TooLong(256))
+        );
+    }
+}
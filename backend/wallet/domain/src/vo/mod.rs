@@ -1,7 +1,17 @@
 mod base;
+mod currency;
 mod id;
+mod memo;
 mod money;
+mod owner;
+mod transaction_kind;
+mod version;
 
 pub use base::*;
+pub use currency::*;
 pub use id::*;
+pub use memo::*;
 pub use money::*;
+pub use owner::*;
+pub use transaction_kind::*;
+pub use version::*;
@@ -1,39 +1,230 @@
-use crate::vo::ValueObject;
-use anyhow::Error;
-use rust_decimal::Decimal;
-use std::marker::PhantomData;
+use crate::vo::{Currency, ValueObject};
+use getset::CopyGetters;
+use rust_decimal::{Decimal, RoundingStrategy};
+use std::cmp::Ordering;
+use std::fmt;
 use std::str::FromStr;
+use thiserror::Error;
 
-#[allow(dead_code)]
-#[derive(Default, Clone, Debug, PartialEq, Eq)]
-pub struct JPY {}
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum MoneyError {
+    #[error("invalid money format: {0}")]
+    InvalidFormat(String),
+    #[error("{currency} supports at most {minor_unit} decimal places")]
+    InvalidScale { currency: Currency, minor_unit: u32 },
+    #[error("currency mismatch: {0} and {1}")]
+    CurrencyMismatch(Currency, Currency),
+    #[error("arithmetic overflow")]
+    Overflow,
+    #[error("ratios must contain at least one non-zero value")]
+    InvalidRatios,
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoundingMode {
+    /// Round half to even (banker's rounding).
+    #[default]
+    HalfEven,
+    /// Round half away from zero.
+    HalfUp,
+    /// Round half toward zero.
+    HalfDown,
+    /// Round away from zero.
+    Up,
+    /// Round toward zero (truncate).
+    Down,
+    /// Round toward positive infinity.
+    Ceiling,
+    /// Round toward negative infinity.
+    Floor,
+}
+
+impl From<RoundingMode> for RoundingStrategy {
+    fn from(mode: RoundingMode) -> Self {
+        match mode {
+            RoundingMode::HalfEven => RoundingStrategy::MidpointNearestEven,
+            RoundingMode::HalfUp => RoundingStrategy::MidpointAwayFromZero,
+            RoundingMode::HalfDown => RoundingStrategy::MidpointTowardZero,
+            RoundingMode::Up => RoundingStrategy::AwayFromZero,
+            RoundingMode::Down => RoundingStrategy::ToZero,
+            RoundingMode::Ceiling => RoundingStrategy::ToPositiveInfinity,
+            RoundingMode::Floor => RoundingStrategy::ToNegativeInfinity,
+        }
+    }
+}
+
+/// An amount held at the scale of its currency's minor unit, e.g. `"1000 JPY"` or `"12.34 USD"`.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, CopyGetters)]
+pub struct Money {
+    #[getset(get_copy = "pub")]
+    amount: Decimal,
+
+    #[getset(get_copy = "pub")]
+    currency: Currency,
+}
+
+impl ValueObject for Money {}
+
+impl Money {
+    pub fn new(amount: Decimal, currency: Currency) -> Result<Self, MoneyError> {
+        let minor_unit = currency.minor_unit();
+        if amount.normalize().scale() > minor_unit {
+            return Err(MoneyError::InvalidScale {
+                currency,
+                minor_unit,
+            });
+        }
+
+        Ok(Self::rescaled(amount, currency))
+    }
+
+    /// Builds money from an amount that may have more decimal places than the currency allows,
+    /// rounding it to the minor unit with `mode`.
+    pub fn with_rounding(amount: Decimal, currency: Currency, mode: RoundingMode) -> Self {
+        let amount = amount.round_dp_with_strategy(currency.minor_unit(), mode.into());
+
+        Self::rescaled(amount, currency)
+    }
+
+    pub fn zero(currency: Currency) -> Self {
+        Self::rescaled(Decimal::ZERO, currency)
+    }
+
+    // an amount too large to hold the minor unit's decimal places keeps as many as fit
+    fn rescaled(mut amount: Decimal, currency: Currency) -> Self {
+        amount.rescale(currency.minor_unit());
 
-#[allow(dead_code)]
-#[derive(Default, Clone, Debug, PartialEq, Eq)]
-pub struct USD {}
+        Self { amount, currency }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.amount.is_zero()
+    }
+
+    pub fn is_positive(&self) -> bool {
+        self.amount.is_sign_positive() && !self.amount.is_zero()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.amount.is_sign_negative() && !self.amount.is_zero()
+    }
+
+    pub fn checked_cmp(&self, other: &Self) -> Result<Ordering, MoneyError> {
+        self.ensure_same_currency(other)?;
+
+        Ok(self.amount.cmp(&other.amount))
+    }
+
+    pub fn checked_add(&self, other: &Self) -> Result<Self, MoneyError> {
+        self.ensure_same_currency(other)?;
+
+        match self.amount.checked_add(other.amount) {
+            Some(amount) => Ok(Self::rescaled(amount, self.currency)),
+            None => Err(MoneyError::Overflow),
+        }
+    }
+
+    pub fn checked_sub(&self, other: &Self) -> Result<Self, MoneyError> {
+        self.ensure_same_currency(other)?;
+
+        match self.amount.checked_sub(other.amount) {
+            Some(amount) => Ok(Self::rescaled(amount, self.currency)),
+            None => Err(MoneyError::Overflow),
+        }
+    }
+
+    pub fn checked_mul(&self, factor: Decimal, mode: RoundingMode) -> Result<Self, MoneyError> {
+        match self.amount.checked_mul(factor) {
+            Some(amount) => Ok(Self::with_rounding(amount, self.currency, mode)),
+            None => Err(MoneyError::Overflow),
+        }
+    }
 
-#[derive(Default, Clone, Debug, Eq)]
-pub struct Money<E>(Decimal, PhantomData<E>);
+    /// Splits the amount into parts proportional to `ratios` without losing a minor unit: the
+    /// parts always sum to the original amount, and leftover units go to the parts with the
+    /// largest remainders (earlier parts first on ties).
+    pub fn allocate(&self, ratios: &[u32]) -> Result<Vec<Self>, MoneyError> {
+        let total = ratios.iter().map(|r| i128::from(*r)).sum::<i128>();
+        if total == 0 {
+            return Err(MoneyError::InvalidRatios);
+        }
 
-impl<E> ValueObject for Money<E> {}
+        // the mantissa counts units of the amount's own scale, which is the minor unit's unless
+        // the amount is too large to hold that many decimal places
+        let scale = self.amount.scale();
+        let units = self.amount.mantissa();
+        let mut parts = Vec::with_capacity(ratios.len());
+        let mut remainders = Vec::with_capacity(ratios.len());
+        for ratio in ratios {
+            let weighted = units
+                .checked_mul(i128::from(*ratio))
+                .ok_or(MoneyError::Overflow)?;
+            parts.push(weighted / total);
+            remainders.push(weighted % total);
+        }
 
-impl<E> FromStr for Money<E> {
-    type Err = Error;
+        let mut leftover = units - parts.iter().sum::<i128>();
+        let mut order = (0..ratios.len()).collect::<Vec<_>>();
+        order.sort_by(|a, b| remainders[*b].abs().cmp(&remainders[*a].abs()));
+        for i in order {
+            if leftover == 0 {
+                break;
+            }
+            if ratios[i] == 0 {
+                continue;
+            }
+            parts[i] += leftover.signum();
+            leftover -= leftover.signum();
+        }
+
+        Ok(parts
+            .into_iter()
+            .map(|units| Self::rescaled(Decimal::from_i128_with_scale(units, scale), self.currency))
+            .collect())
+    }
+
+    pub fn allocate_to(&self, n: usize) -> Result<Vec<Self>, MoneyError> {
+        self.allocate(&vec![1; n])
+    }
+
+    fn ensure_same_currency(&self, other: &Self) -> Result<(), MoneyError> {
+        if self.currency != other.currency {
+            return Err(MoneyError::CurrencyMismatch(self.currency, other.currency));
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Money {
+    type Err = MoneyError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Self(Decimal::from_str(s)?, PhantomData))
+        let mut parts = s.split_whitespace();
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(amount), Some(currency), None) => {
+                let amount = Decimal::from_str(amount)
+                    .map_err(|_| MoneyError::InvalidFormat(s.to_string()))?;
+                let currency = currency
+                    .parse::<Currency>()
+                    .map_err(|_| MoneyError::InvalidFormat(s.to_string()))?;
+
+                Self::new(amount, currency)
+            }
+            _ => Err(MoneyError::InvalidFormat(s.to_string())),
+        }
     }
 }
 
-impl<T> ToString for Money<T> {
-    fn to_string(&self) -> String {
-        self.0.clone().to_string()
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.amount, self.currency)
     }
 }
 
-impl<T> PartialEq for Money<T> {
-    fn eq(&self, other: &Self) -> bool {
-        self.0 == other.0
+impl PartialOrd for Money {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.checked_cmp(other).ok()
     }
 }
 
@@ -43,25 +234,296 @@ mod tests {
 
     #[test]
     fn test_money_from_str() {
-        assert!("1000".parse::<Money::<JPY>>().is_ok());
-        assert!("1000".parse::<Money::<USD>>().is_ok());
+        assert!("1000 JPY".parse::<Money>().is_ok());
+        assert!("1000 USD".parse::<Money>().is_ok());
+        assert!("10.50 USD".parse::<Money>().is_ok());
+        assert!("10.500 USD".parse::<Money>().is_ok());
+        assert!("1.0 JPY".parse::<Money>().is_ok());
+
+        // err
+        assert!("1000".parse::<Money>().is_err());
+        assert!("1000 XXX".parse::<Money>().is_err());
+        assert!("1000 JPY USD".parse::<Money>().is_err());
+        assert_eq!(
+            "1.5 JPY".parse::<Money>(),
+            Err(MoneyError::InvalidScale {
+                currency: Currency::JPY,
+                minor_unit: 0
+            })
+        );
+        assert!("10.505 USD".parse::<Money>().is_err());
     }
 
     #[test]
     fn test_money_to_string() {
         assert_eq!(
-            "1000".parse::<Money::<JPY>>().unwrap().to_string(),
-            "1000".to_string()
+            "1000 JPY".parse::<Money>().unwrap().to_string(),
+            "1000 JPY".to_string()
         );
+        assert_eq!(
+            "10.5 USD".parse::<Money>().unwrap().to_string(),
+            "10.50 USD".to_string()
+        );
+    }
+
+    #[test]
+    fn test_money_amount_currency() {
+        let money = "10.5 USD".parse::<Money>().unwrap();
+
+        assert_eq!(money.amount().to_string(), "10.50".to_string());
+        assert_eq!(money.currency(), Currency::USD);
     }
 
     #[test]
     fn test_money_eq() {
-        let jpy = "1000".parse::<Money<JPY>>().unwrap();
-        let cloned = jpy.clone();
-        let _usd = "1000".parse::<Money<USD>>().unwrap();
+        let jpy = "1000 JPY".parse::<Money>().unwrap();
+        let cloned = jpy;
+        let usd = "1000 USD".parse::<Money>().unwrap();
 
         assert_eq!(jpy, cloned);
-        // assert_ne!(jpy, _usd); // expected struct `money::JPY`, found struct `money::USD`
+        assert_ne!(jpy, usd);
+        assert_eq!(
+            "10 USD".parse::<Money>().unwrap(),
+            "10.00 USD".parse::<Money>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_money_ord() {
+        let small = "1000 JPY".parse::<Money>().unwrap();
+        let large = "2000 JPY".parse::<Money>().unwrap();
+        let usd = "2000 USD".parse::<Money>().unwrap();
+
+        assert!(small < large);
+        assert!(large > small);
+        assert_eq!(small.checked_cmp(&large), Ok(Ordering::Less));
+        assert_eq!(small.partial_cmp(&usd), None);
+        assert_eq!(
+            small.checked_cmp(&usd),
+            Err(MoneyError::CurrencyMismatch(Currency::JPY, Currency::USD))
+        );
+    }
+
+    #[test]
+    fn test_money_add_sub() {
+        let a = "1000 JPY".parse::<Money>().unwrap();
+        let b = "300 JPY".parse::<Money>().unwrap();
+        let usd = "300 USD".parse::<Money>().unwrap();
+
+        assert_eq!(
+            a.checked_add(&b).unwrap(),
+            "1300 JPY".parse::<Money>().unwrap()
+        );
+        assert_eq!(
+            a.checked_sub(&b).unwrap(),
+            "700 JPY".parse::<Money>().unwrap()
+        );
+        assert_eq!(
+            a.checked_add(&usd),
+            Err(MoneyError::CurrencyMismatch(Currency::JPY, Currency::USD))
+        );
+        assert_eq!(
+            a.checked_sub(&usd),
+            Err(MoneyError::CurrencyMismatch(Currency::JPY, Currency::USD))
+        );
+    }
+
+    #[test]
+    fn test_money_overflow() {
+        let max = Money::new(Decimal::MAX, Currency::JPY).unwrap();
+        let min = Money::new(Decimal::MIN, Currency::JPY).unwrap();
+        let one = "1 JPY".parse::<Money>().unwrap();
+
+        assert_eq!(max.checked_add(&one), Err(MoneyError::Overflow));
+        assert_eq!(min.checked_sub(&one), Err(MoneyError::Overflow));
+        assert_eq!(
+            max.checked_mul(Decimal::from(2), RoundingMode::HalfEven),
+            Err(MoneyError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_money_mul() {
+        let usd = "10.00 USD".parse::<Money>().unwrap();
+        let jpy = "25 JPY".parse::<Money>().unwrap();
+        let half = Decimal::from_str("0.5").unwrap();
+
+        assert_eq!(
+            usd.checked_mul(Decimal::from_str("0.333").unwrap(), RoundingMode::HalfEven)
+                .unwrap(),
+            "3.33 USD".parse::<Money>().unwrap()
+        );
+        assert_eq!(
+            jpy.checked_mul(half, RoundingMode::HalfEven).unwrap(),
+            "12 JPY".parse::<Money>().unwrap()
+        );
+        assert_eq!(
+            jpy.checked_mul(half, RoundingMode::HalfUp).unwrap(),
+            "13 JPY".parse::<Money>().unwrap()
+        );
+        assert_eq!(
+            jpy.checked_mul(half, RoundingMode::HalfDown).unwrap(),
+            "12 JPY".parse::<Money>().unwrap()
+        );
+        assert_eq!(
+            jpy.checked_mul(half, RoundingMode::Up).unwrap(),
+            "13 JPY".parse::<Money>().unwrap()
+        );
+        assert_eq!(
+            jpy.checked_mul(half, RoundingMode::Down).unwrap(),
+            "12 JPY".parse::<Money>().unwrap()
+        );
+        assert_eq!(
+            jpy.checked_mul(-half, RoundingMode::Ceiling).unwrap(),
+            "-12 JPY".parse::<Money>().unwrap()
+        );
+        assert_eq!(
+            jpy.checked_mul(-half, RoundingMode::Floor).unwrap(),
+            "-13 JPY".parse::<Money>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_money_with_rounding() {
+        assert_eq!(
+            Money::with_rounding(
+                Decimal::from_str("1.005").unwrap(),
+                Currency::USD,
+                RoundingMode::HalfUp
+            ),
+            "1.01 USD".parse::<Money>().unwrap()
+        );
+        assert_eq!(
+            Money::with_rounding(
+                Decimal::from_str("1.005").unwrap(),
+                Currency::USD,
+                RoundingMode::HalfEven
+            ),
+            "1.00 USD".parse::<Money>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_money_allocate() {
+        let jpy = "100 JPY".parse::<Money>().unwrap();
+
+        assert_eq!(
+            jpy.allocate_to(3).unwrap(),
+            vec![
+                "34 JPY".parse::<Money>().unwrap(),
+                "33 JPY".parse::<Money>().unwrap(),
+                "33 JPY".parse::<Money>().unwrap(),
+            ]
+        );
+        assert_eq!(
+            jpy.allocate(&[1, 0, 2]).unwrap(),
+            vec![
+                "33 JPY".parse::<Money>().unwrap(),
+                "0 JPY".parse::<Money>().unwrap(),
+                "67 JPY".parse::<Money>().unwrap(),
+            ]
+        );
+        assert_eq!(
+            "0.05 USD"
+                .parse::<Money>()
+                .unwrap()
+                .allocate(&[3, 7])
+                .unwrap(),
+            vec![
+                "0.02 USD".parse::<Money>().unwrap(),
+                "0.03 USD".parse::<Money>().unwrap(),
+            ]
+        );
+        assert_eq!(
+            "-100 JPY".parse::<Money>().unwrap().allocate_to(3).unwrap(),
+            vec![
+                "-34 JPY".parse::<Money>().unwrap(),
+                "-33 JPY".parse::<Money>().unwrap(),
+                "-33 JPY".parse::<Money>().unwrap(),
+            ]
+        );
+
+        // err
+        assert_eq!(jpy.allocate(&[]), Err(MoneyError::InvalidRatios));
+        assert_eq!(jpy.allocate(&[0, 0]), Err(MoneyError::InvalidRatios));
+    }
+
+    #[test]
+    fn test_money_allocate_sums_to_original() {
+        let jpy = "1234567 JPY".parse::<Money>().unwrap();
+
+        for ratios in [vec![1, 1, 1], vec![7, 3, 5, 11], vec![1, 999]] {
+            let total = jpy
+                .allocate(&ratios)
+                .unwrap()
+                .iter()
+                .try_fold(Money::zero(Currency::JPY), |acc, m| acc.checked_add(m))
+                .unwrap();
+
+            assert_eq!(total, jpy);
+        }
+    }
+
+    #[test]
+    fn test_money_allocate_large_amount() {
+        // too large to be held in cents, so it is held in whole dollars
+        let usd = "79228162514264337593543950335 USD"
+            .parse::<Money>()
+            .unwrap();
+        assert_eq!(usd.amount().scale(), 0);
+
+        let parts = usd.allocate_to(2).unwrap();
+        assert_eq!(
+            parts,
+            vec![
+                "39614081257132168796771975168 USD"
+                    .parse::<Money>()
+                    .unwrap(),
+                "39614081257132168796771975167 USD"
+                    .parse::<Money>()
+                    .unwrap(),
+            ]
+        );
+        assert_eq!(parts[0].checked_add(&parts[1]).unwrap(), usd);
+
+        // one that fits in cents is split to the cent
+        let usd = "792281625142643375935439503.35 USD"
+            .parse::<Money>()
+            .unwrap();
+        assert_eq!(
+            usd.allocate_to(2).unwrap(),
+            vec![
+                "396140812571321687967719751.68 USD"
+                    .parse::<Money>()
+                    .unwrap(),
+                "396140812571321687967719751.67 USD"
+                    .parse::<Money>()
+                    .unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_money_zero() {
+        assert_eq!(Money::zero(Currency::JPY).to_string(), "0 JPY".to_string());
+        assert_eq!(
+            Money::zero(Currency::USD).to_string(),
+            "0.00 USD".to_string()
+        );
+    }
+
+    #[test]
+    fn test_money_predicates() {
+        assert!("1 JPY".parse::<Money>().unwrap().is_positive());
+        assert!(!"0 JPY".parse::<Money>().unwrap().is_positive());
+        assert!(!"-1 JPY".parse::<Money>().unwrap().is_positive());
+
+        assert!("-1 JPY".parse::<Money>().unwrap().is_negative());
+        assert!(!"0 JPY".parse::<Money>().unwrap().is_negative());
+        assert!(!"-0 JPY".parse::<Money>().unwrap().is_negative());
+
+        assert!("0 JPY".parse::<Money>().unwrap().is_zero());
+        assert!("0.00 USD".parse::<Money>().unwrap().is_zero());
+        assert!(!"0.01 USD".parse::<Money>().unwrap().is_zero());
     }
 }
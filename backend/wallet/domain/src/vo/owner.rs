@@ -0,0 +1,85 @@
+use crate::vo::ValueObject;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+const OWNER_MAX_LENGTH: usize = 64;
+
+static OWNER_FORMAT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[A-Za-z0-9][A-Za-z0-9._-]*$").unwrap());
+
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum OwnerError {
+    #[error("owner must not be empty")]
+    Empty,
+    #[error("owner must be at most {} characters, got {0}", OWNER_MAX_LENGTH)]
+    TooLong(usize),
+    #[error("owner must start with an alphanumeric character and contain only alphanumerics, '.', '_' or '-'")]
+    InvalidFormat,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct Owner(String);
+
+impl ValueObject for Owner {}
+
+impl FromStr for Owner {
+    type Err = OwnerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let length = s.chars().count();
+        if length == 0 {
+            return Err(OwnerError::Empty);
+        }
+        if length > OWNER_MAX_LENGTH {
+            return Err(OwnerError::TooLong(length));
+        }
+        if !OWNER_FORMAT.is_match(s) {
+            return Err(OwnerError::InvalidFormat);
+        }
+
+        Ok(Self(s.to_string()))
+    }
+}
+
+impl fmt::Display for Owner {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_owner() {
+        assert!("alice".parse::<Owner>().is_ok());
+        assert!("kzmake".parse::<Owner>().is_ok());
+        assert!("alice.bob_01-x".parse::<Owner>().is_ok());
+        assert!("a".repeat(64).parse::<Owner>().is_ok());
+    }
+
+    #[test]
+    fn test_owner_err() {
+        assert_eq!("".parse::<Owner>(), Err(OwnerError::Empty));
+        assert_eq!(
+            "a".repeat(65).parse::<Owner>(),
+            Err(OwnerError::TooLong(65))
+        );
+        assert_eq!(" alice".parse::<Owner>(), Err(OwnerError::InvalidFormat));
+        assert_eq!("-alice".parse::<Owner>(), Err(OwnerError::InvalidFormat));
+        assert_eq!("ali ce".parse::<Owner>(), Err(OwnerError::InvalidFormat));
+        assert_eq!("alice@".parse::<Owner>(), Err(OwnerError::InvalidFormat));
+    }
+
+    #[test]
+    fn test_owner_to_string() {
+        assert_eq!(
+            "alice".parse::<Owner>().unwrap().to_string(),
+            "alice".to_string()
+        );
+    }
+}
@@ -0,0 +1,41 @@
+use crate::vo::ValueObject;
+use parse_display::{Display, FromStr};
+
+/// The operation a transaction records, seen from the wallet it belongs to.
+#[derive(Display, FromStr, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[display(style = "SNAKE_CASE")]
+pub enum TransactionKind {
+    Deposit,
+    Withdraw,
+    TransferIn,
+    TransferOut,
+}
+
+impl ValueObject for TransactionKind {}
+
+impl TransactionKind {
+    /// Whether the transaction adds to the balance.
+    pub fn is_credit(&self) -> bool {
+        matches!(self, Self::Deposit | Self::TransferIn)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transaction_kind() {
+        assert_eq!(TransactionKind::Deposit.to_string(), "DEPOSIT");
+        assert_eq!(TransactionKind::TransferOut.to_string(), "TRANSFER_OUT");
+        assert_eq!(
+            "TRANSFER_IN".parse::<TransactionKind>().unwrap(),
+            TransactionKind::TransferIn
+        );
+        assert!(TransactionKind::Deposit.is_credit());
+        assert!(!TransactionKind::Withdraw.is_credit());
+
+        // err
+        assert!("deposit".parse::<TransactionKind>().is_err());
+    }
+}
@@ -0,0 +1,80 @@
+use crate::vo::ValueObject;
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum VersionError {
+    #[error("version must be a positive integer, got {0:?}")]
+    InvalidFormat(String),
+}
+
+/// Revision of an aggregate, bumped by the repository on every successful write and used to
+/// detect concurrent modifications.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version(u64);
+
+impl ValueObject for Version {}
+
+impl Version {
+    pub fn new(version: u64) -> Self {
+        Self(version)
+    }
+
+    pub fn value(&self) -> u64 {
+        self.0
+    }
+
+    pub fn next(&self) -> Self {
+        Self(self.0 + 1)
+    }
+}
+
+impl Default for Version {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+impl FromStr for Version {
+    type Err = VersionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.parse::<u64>() {
+            Ok(version) if version > 0 => Ok(Self(version)),
+            _ => Err(VersionError::InvalidFormat(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version() {
+        assert_eq!(Version::default(), Version::new(1));
+        assert_eq!(Version::new(1).next(), Version::new(2));
+        assert_eq!(Version::new(2).to_string(), "2".to_string());
+    }
+
+    #[test]
+    fn test_version_from_str() {
+        assert_eq!("3".parse::<Version>().unwrap(), Version::new(3));
+
+        // err
+        assert_eq!(
+            "0".parse::<Version>().unwrap_err(),
+            VersionError::InvalidFormat("0".to_string())
+        );
+        assert!("".parse::<Version>().is_err());
+        assert!("-1".parse::<Version>().is_err());
+        assert!("\"1\"".parse::<Version>().is_err());
+    }
+}
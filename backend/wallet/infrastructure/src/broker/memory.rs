@@ -0,0 +1,45 @@
+use crate::broker::{Broker, BrokerError, Message};
+use std::sync::{Arc, Mutex};
+
+/// Keeps published messages in memory, for tests and for running without a broker.
+#[derive(Clone, Default)]
+pub struct InMemoryBroker {
+    messages: Arc<Mutex<Vec<Message>>>,
+}
+
+impl InMemoryBroker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn messages(&self) -> Vec<Message> {
+        self.messages.lock().unwrap().clone()
+    }
+}
+
+impl Broker for InMemoryBroker {
+    fn publish(&self, message: &Message) -> Result<(), BrokerError> {
+        self.messages.lock().unwrap().push(message.clone());
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_memory_broker_publish() {
+        let sut = InMemoryBroker::new();
+        let message = Message {
+            id: "1".to_string(),
+            subject: "wallet.WalletCreated".to_string(),
+            payload: b"{}".to_vec(),
+        };
+
+        sut.clone().publish(&message).unwrap();
+
+        assert_eq!(sut.messages(), vec![message]);
+    }
+}
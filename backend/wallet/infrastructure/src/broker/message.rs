@@ -0,0 +1,24 @@
+use thiserror::Error;
+
+/// An event on its way to downstream systems. `id` is unique per message, so consumers can drop
+/// the duplicates an at-least-once delivery may produce.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Message {
+    pub id: String,
+    pub subject: String,
+    pub payload: Vec<u8>,
+}
+
+#[derive(Error, Debug)]
+pub enum BrokerError {
+    #[error("failed to talk to the broker: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("the broker rejected the message: {0}")]
+    Rejected(String),
+}
+
+/// Somewhere messages can be published to. `publish` returns once the broker has accepted the
+/// message, so a message that failed to publish can be published again.
+pub trait Broker: Send + Sync {
+    fn publish(&self, message: &Message) -> Result<(), BrokerError>;
+}
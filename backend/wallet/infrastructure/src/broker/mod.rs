@@ -0,0 +1,7 @@
+mod memory;
+mod message;
+mod nats;
+
+pub use self::memory::*;
+pub use self::message::*;
+pub use self::nats::*;
@@ -0,0 +1,211 @@
+use crate::broker::{Broker, BrokerError, Message};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::Mutex;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Publishes to a NATS server over its text protocol. The connection is opened on first use and
+/// opened again after any failure.
+pub struct NatsBroker {
+    addr: String,
+    connection: Mutex<Option<Connection>>,
+}
+
+struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl NatsBroker {
+    pub fn new(addr: impl Into<String>) -> Self {
+        Self {
+            addr: addr.into(),
+            connection: Mutex::new(None),
+        }
+    }
+
+    fn connect(&self) -> Result<Connection, BrokerError> {
+        let addr = self.addr.to_socket_addrs()?.next().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::AddrNotAvailable,
+                format!("{} does not resolve", self.addr),
+            )
+        })?;
+        let stream = TcpStream::connect_timeout(&addr, TIMEOUT)?;
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+
+        let mut connection = Connection {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+        };
+        let greeting = connection.read_line()?;
+        if !greeting.starts_with("INFO") {
+            return Err(BrokerError::Rejected(format!(
+                "unexpected greeting {}",
+                greeting
+            )));
+        }
+        connection.writer.write_all(
+            b"CONNECT {\"verbose\":false,\"pedantic\":false,\"name\":\"wallet-outbox\"}\r\nPING\r\n",
+        )?;
+        connection.await_pong()?;
+
+        Ok(connection)
+    }
+}
+
+impl Connection {
+    fn read_line(&mut self) -> Result<String, BrokerError> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "connection closed").into());
+        }
+
+        Ok(line.trim_end().to_string())
+    }
+
+    // the server handles a connection's commands in order, so once the PONG for a PING sent
+    // after a PUB arrives the PUB has been processed
+    fn await_pong(&mut self) -> Result<(), BrokerError> {
+        loop {
+            let line = self.read_line()?;
+            match line.as_str() {
+                "PONG" => return Ok(()),
+                "PING" => self.writer.write_all(b"PONG\r\n")?,
+                "+OK" => {}
+                line if line.starts_with("INFO") => {}
+                line if line.starts_with("-ERR") => {
+                    return Err(BrokerError::Rejected(
+                        line["-ERR".len()..].trim().trim_matches('\'').to_string(),
+                    ))
+                }
+                line => return Err(BrokerError::Rejected(format!("unexpected reply {}", line))),
+            }
+        }
+    }
+
+    fn publish(&mut self, message: &Message) -> Result<(), BrokerError> {
+        let mut buf = format!("PUB {} {}\r\n", message.subject, message.payload.len()).into_bytes();
+        buf.extend_from_slice(&message.payload);
+        buf.extend_from_slice(b"\r\nPING\r\n");
+        self.writer.write_all(&buf)?;
+        self.writer.flush()?;
+
+        self.await_pong()
+    }
+}
+
+impl Broker for NatsBroker {
+    fn publish(&self, message: &Message) -> Result<(), BrokerError> {
+        let mut connection = self.connection.lock().unwrap();
+        if connection.is_none() {
+            *connection = Some(self.connect()?);
+        }
+
+        let result = connection.as_mut().unwrap().publish(message);
+        if result.is_err() {
+            *connection = None;
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::net::TcpListener;
+    use std::sync::mpsc::{self, Sender};
+    use std::thread;
+
+    // just enough of a NATS server to accept publishes, refusing those to `forbidden`
+    fn serve(listener: TcpListener, published: Sender<(String, Vec<u8>)>) {
+        for stream in listener.incoming() {
+            let mut writer = stream.unwrap();
+            let mut reader = BufReader::new(writer.try_clone().unwrap());
+            writer
+                .write_all(b"INFO {\"server_id\":\"stand-in\"}\r\n")
+                .unwrap();
+
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 0 {
+                let args = line.split_whitespace().collect::<Vec<_>>();
+                match args.as_slice() {
+                    ["PING"] => writer.write_all(b"PONG\r\n").unwrap(),
+                    ["PUB", subject, len] => {
+                        let mut payload = vec![0; len.parse::<usize>().unwrap() + 2];
+                        reader.read_exact(&mut payload).unwrap();
+                        payload.truncate(payload.len() - 2);
+                        if *subject == "forbidden" {
+                            writer
+                                .write_all(b"-ERR 'Permissions Violation for Publish'\r\n")
+                                .unwrap();
+                            break;
+                        }
+                        published.send((subject.to_string(), payload)).unwrap();
+                    }
+                    _ => {}
+                }
+                line.clear();
+            }
+        }
+    }
+
+    fn message(subject: &str, payload: &str) -> Message {
+        Message {
+            id: "1".to_string(),
+            subject: subject.to_string(),
+            payload: payload.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_nats_broker_publish() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || serve(listener, tx));
+
+        let sut = NatsBroker::new(addr.to_string());
+
+        sut.publish(&message("wallet.WalletCreated", "{\"owner\":\"alice\"}"))
+            .unwrap();
+        assert_eq!(
+            rx.recv().unwrap(),
+            (
+                "wallet.WalletCreated".to_string(),
+                b"{\"owner\":\"alice\"}".to_vec()
+            )
+        );
+
+        // err
+        assert!(matches!(
+            sut.publish(&message("forbidden", "{}")).unwrap_err(),
+            BrokerError::Rejected(reason) if reason == "Permissions Violation for Publish"
+        ));
+
+        // reconnects after the failure
+        sut.publish(&message("wallet.WalletDeleted", "{}")).unwrap();
+        assert_eq!(rx.recv().unwrap().0, "wallet.WalletDeleted");
+    }
+
+    #[test]
+    fn test_nats_broker_publish_unreachable() {
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+
+        let sut = NatsBroker::new(addr.to_string());
+
+        assert!(matches!(
+            sut.publish(&message("wallet.WalletCreated", "{}"))
+                .unwrap_err(),
+            BrokerError::Io(_)
+        ));
+    }
+}
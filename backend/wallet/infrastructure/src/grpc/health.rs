@@ -0,0 +1,217 @@
+use interface::grpc_health_v1::health_check_response::ServingStatus;
+use interface::grpc_health_v1::health_server;
+use interface::grpc_health_v1::{HealthCheckRequest, HealthCheckResponse};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use tokio::sync::watch;
+use tokio_stream::wrappers::WatchStream;
+use tokio_stream::{Stream, StreamExt};
+use tonic::{Request, Response, Status};
+use tracing::{info, warn};
+
+/// The services health is reported for: the server as a whole, named by the empty string, and
+/// the wallet service.
+const SERVICES: [&str; 2] = ["", "osaifu.wallet.v1.WalletService"];
+
+/// Answers the standard gRPC health checks (`grpc.health.v1.Health`) for the wallet service.
+///
+/// It reports `NOT_SERVING` until the migrations have run, and after that whatever the latest
+/// database probe found, until the service starts shutting down. Clones share the status, so
+/// the probes update what the server answers.
+#[derive(Clone)]
+pub struct Health {
+    state: Arc<Mutex<State>>,
+    sender: Arc<watch::Sender<ServingStatus>>,
+    // kept so that sending never fails for want of a receiver
+    receiver: watch::Receiver<ServingStatus>,
+}
+
+#[derive(Default)]
+struct State {
+    migrated: bool,
+    database: bool,
+    shutting_down: bool,
+}
+
+impl State {
+    fn status(&self) -> ServingStatus {
+        match self.migrated && self.database && !self.shutting_down {
+            true => ServingStatus::Serving,
+            false => ServingStatus::NotServing,
+        }
+    }
+}
+
+impl Health {
+    pub fn new() -> Self {
+        let (sender, receiver) = watch::channel(ServingStatus::NotServing);
+
+        Self {
+            state: Arc::new(Mutex::new(State::default())),
+            sender: Arc::new(sender),
+            receiver,
+        }
+    }
+
+    /// Records that the migrations have run; the database was reachable for them to.
+    pub fn set_migrated(&self) {
+        self.update(|state| {
+            state.migrated = true;
+            state.database = true;
+        });
+    }
+
+    /// Records whether the latest probe reached the database.
+    pub fn set_database(&self, reachable: bool) {
+        self.update(|state| state.database = reachable);
+    }
+
+    /// Stops serving for good, so that load balancers send no new requests while the ones in
+    /// flight finish.
+    pub fn shut_down(&self) {
+        self.update(|state| state.shutting_down = true);
+    }
+
+    pub fn status(&self) -> ServingStatus {
+        *self.receiver.borrow()
+    }
+
+    fn update<F>(&self, f: F)
+    where
+        F: FnOnce(&mut State),
+    {
+        let mut state = self.state.lock().unwrap();
+        f(&mut state);
+
+        let status = state.status();
+        if status != self.status() {
+            match status {
+                ServingStatus::Serving => info!(status = ?status, "health changed"),
+                _ => warn!(status = ?status, "health changed"),
+            }
+            // the receiver held by `self` keeps the channel open
+            self.sender.send(status).unwrap();
+        }
+    }
+}
+
+impl Default for Health {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn response(status: ServingStatus) -> HealthCheckResponse {
+    HealthCheckResponse {
+        status: status as i32,
+    }
+}
+
+#[tonic::async_trait]
+impl health_server::Health for Health {
+    async fn check(
+        &self,
+        request: Request<HealthCheckRequest>,
+    ) -> Result<Response<HealthCheckResponse>, Status> {
+        let service = &request.get_ref().service;
+        if !SERVICES.contains(&service.as_str()) {
+            return Err(Status::not_found(format!("unknown service: {}", service)));
+        }
+
+        Ok(Response::new(response(self.status())))
+    }
+
+    type WatchStream =
+        Pin<Box<dyn Stream<Item = Result<HealthCheckResponse, Status>> + Send + 'static>>;
+
+    async fn watch(
+        &self,
+        request: Request<HealthCheckRequest>,
+    ) -> Result<Response<Self::WatchStream>, Status> {
+        // an unknown service is reported as such, and the call is kept open all the same
+        if !SERVICES.contains(&request.get_ref().service.as_str()) {
+            let stream = tokio_stream::once(Ok(response(ServingStatus::ServiceUnknown)))
+                .chain(tokio_stream::pending());
+            return Ok(Response::new(Box::pin(stream)));
+        }
+
+        // the current status first, then every change
+        let stream = WatchStream::new(self.receiver.clone())
+            .map(response)
+            .map(Ok);
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use health_server::Health as _;
+
+    fn request(service: &str) -> Request<HealthCheckRequest> {
+        Request::new(HealthCheckRequest {
+            service: service.to_string(),
+        })
+    }
+
+    async fn check(sut: &Health, service: &str) -> ServingStatus {
+        let response = sut.check(request(service)).await.unwrap();
+        ServingStatus::from_i32(response.get_ref().status).unwrap()
+    }
+
+    async fn next(stream: &mut <Health as health_server::Health>::WatchStream) -> ServingStatus {
+        let response = stream.next().await.unwrap().unwrap();
+        ServingStatus::from_i32(response.status).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_health_check() {
+        let sut = Health::new();
+
+        // not until the migrations have run
+        assert_eq!(check(&sut, "").await, ServingStatus::NotServing);
+
+        sut.set_migrated();
+        assert_eq!(check(&sut, "").await, ServingStatus::Serving);
+        assert_eq!(
+            check(&sut, "osaifu.wallet.v1.WalletService").await,
+            ServingStatus::Serving
+        );
+
+        // nor while the database is out of reach, as a probe holding a clone finds
+        let probe = sut.clone();
+        probe.set_database(false);
+        assert_eq!(check(&sut, "").await, ServingStatus::NotServing);
+
+        sut.set_database(true);
+        assert_eq!(check(&sut, "").await, ServingStatus::Serving);
+
+        // not once shutting down, whatever the probes find
+        sut.shut_down();
+        assert_eq!(check(&sut, "").await, ServingStatus::NotServing);
+        sut.set_database(true);
+        assert_eq!(check(&sut, "").await, ServingStatus::NotServing);
+
+        // err: unknown service
+        let status = sut.check(request("unknown")).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_health_watch() {
+        let sut = Health::new();
+
+        let mut stream = sut.watch(request("")).await.unwrap().into_inner();
+        assert_eq!(next(&mut stream).await, ServingStatus::NotServing);
+
+        sut.set_migrated();
+        assert_eq!(next(&mut stream).await, ServingStatus::Serving);
+
+        sut.set_database(false);
+        assert_eq!(next(&mut stream).await, ServingStatus::NotServing);
+
+        // an unknown service is reported, not refused
+        let mut stream = sut.watch(request("unknown")).await.unwrap().into_inner();
+        assert_eq!(next(&mut stream).await, ServingStatus::ServiceUnknown);
+    }
+}
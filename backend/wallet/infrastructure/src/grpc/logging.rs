@@ -0,0 +1,319 @@
+use crate::grpc::tls::client_identity;
+use crate::metrics::Metrics;
+use interface::controller::ClientIdentity;
+use interface::osaifu_wallet_v1::*;
+use std::fmt::Debug;
+use std::future::Future;
+use std::time::Instant;
+use tonic::metadata::MetadataValue;
+use tonic::{Code, Request, Response, Status};
+use tracing::{debug, error, field, info, info_span, warn, Instrument};
+use ulid::Ulid;
+
+/// The metadata key carrying the id a request is logged under. A caller may send one to tie
+/// the service's logs to its own; otherwise one is generated. Either way it is sent back.
+pub const REQUEST_ID: &str = "x-request-id";
+
+const REDACTED: &str = "[REDACTED]";
+
+// longer ids are more likely garbage than something worth searching logs for
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// What a request message says about itself in the span it is handled in.
+pub(crate) trait Loggable: Debug {
+    fn wallet_id(&self) -> Option<&str> {
+        None
+    }
+
+    fn owner(&self) -> Option<&str> {
+        None
+    }
+
+    /// Returns a copy fit for the logs, with free text and secrets blanked out.
+    fn redacted(&self) -> Self;
+}
+
+/// Handles `request` with `handle` in a span carrying the method, the request id, the client
+/// when it presented a certificate and, where the request names them, the wallet and owner.
+/// The client's [`ClientIdentity`] is added to the request that `handle` gets. How long the
+/// request took and how it ended are logged and recorded in `metrics`.
+pub(crate) async fn traced<Req, Res, F, Fut>(
+    metrics: &Metrics,
+    method: &'static str,
+    mut request: Request<Req>,
+    handle: F,
+) -> Result<Response<Res>, Status>
+where
+    Req: Loggable,
+    F: FnOnce(Request<Req>) -> Fut,
+    Fut: Future<Output = Result<Response<Res>, Status>>,
+{
+    let request_id = request_id(&request);
+    let identity = request.peer_certs().and_then(|certs| {
+        certs
+            .first()
+            .and_then(|cert| client_identity(cert.get_ref()))
+    });
+    let span = info_span!(
+        "request",
+        method,
+        request_id = %request_id,
+        client = field::Empty,
+        wallet_id = field::Empty,
+        owner = field::Empty,
+    );
+    if let Some(identity) = identity {
+        span.record("client", identity.name());
+        request.extensions_mut().insert::<ClientIdentity>(identity);
+    }
+    let message = request.get_ref();
+    if let Some(wallet_id) = message.wallet_id().filter(|id| !id.is_empty()) {
+        span.record("wallet_id", wallet_id);
+    }
+    if let Some(owner) = message.owner().filter(|owner| !owner.is_empty()) {
+        span.record("owner", owner);
+    }
+
+    async move {
+        debug!(request = ?request.get_ref().redacted(), "received");
+
+        let started = Instant::now();
+        let mut result = handle(request).await;
+        let latency = started.elapsed();
+        let latency_ms = latency.as_millis() as u64;
+
+        let code = match &result {
+            Ok(_) => Code::Ok,
+            Err(status) => status.code(),
+        };
+        metrics.observe_rpc(method, code, latency);
+
+        match &result {
+            Ok(_) => info!(latency_ms, code = ?Code::Ok, "handled"),
+            Err(status) => match status.code() {
+                Code::Internal | Code::Unknown | Code::Unavailable | Code::DataLoss => error!(
+                    latency_ms,
+                    code = ?status.code(),
+                    message = status.message(),
+                    "failed"
+                ),
+                code => warn!(latency_ms, code = ?code, message = status.message(), "rejected"),
+            },
+        }
+
+        // the id is ASCII, either checked as it came in or generated
+        let value = MetadataValue::from_str(&request_id).unwrap();
+        match &mut result {
+            Ok(response) => response.metadata_mut().insert(REQUEST_ID, value),
+            Err(status) => status.metadata_mut().insert(REQUEST_ID, value),
+        };
+
+        result
+    }
+    .instrument(span)
+    .await
+}
+
+fn request_id<T>(request: &Request<T>) -> String {
+    request
+        .metadata()
+        .get(REQUEST_ID)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN)
+        .map(str::to_string)
+        .unwrap_or_else(|| Ulid::new().to_string())
+}
+
+fn redact(value: &str) -> String {
+    match value {
+        "" => String::new(),
+        _ => REDACTED.to_string(),
+    }
+}
+
+impl Loggable for CreateRequest {
+    fn owner(&self) -> Option<&str> {
+        Some(&self.owner)
+    }
+
+    fn redacted(&self) -> Self {
+        Self {
+            idempotency_key: redact(&self.idempotency_key),
+            ..self.clone()
+        }
+    }
+}
+
+impl Loggable for ListRequest {
+    fn owner(&self) -> Option<&str> {
+        Some(&self.owner)
+    }
+
+    fn redacted(&self) -> Self {
+        self.clone()
+    }
+}
+
+impl Loggable for GetRequest {
+    fn wallet_id(&self) -> Option<&str> {
+        Some(&self.id)
+    }
+
+    fn redacted(&self) -> Self {
+        self.clone()
+    }
+}
+
+impl Loggable for DeleteRequest {
+    fn wallet_id(&self) -> Option<&str> {
+        Some(&self.id)
+    }
+
+    fn redacted(&self) -> Self {
+        Self {
+            idempotency_key: redact(&self.idempotency_key),
+            ..self.clone()
+        }
+    }
+}
+
+impl Loggable for DepositRequest {
+    fn wallet_id(&self) -> Option<&str> {
+        Some(&self.id)
+    }
+
+    fn redacted(&self) -> Self {
+        Self {
+            idempotency_key: redact(&self.idempotency_key),
+            memo: redact(&self.memo),
+            ..self.clone()
+        }
+    }
+}
+
+impl Loggable for WithdrawRequest {
+    fn wallet_id(&self) -> Option<&str> {
+        Some(&self.id)
+    }
+
+    fn redacted(&self) -> Self {
+        Self {
+            idempotency_key: redact(&self.idempotency_key),
+            memo: redact(&self.memo),
+            ..self.clone()
+        }
+    }
+}
+
+impl Loggable for TransferRequest {
+    // the wallet the money leaves; the other one is in the request logged at debug
+    fn wallet_id(&self) -> Option<&str> {
+        Some(&self.from_id)
+    }
+
+    fn redacted(&self) -> Self {
+        Self {
+            idempotency_key: redact(&self.idempotency_key),
+            memo: redact(&self.memo),
+            ..self.clone()
+        }
+    }
+}
+
+impl Loggable for UndeleteRequest {
+    fn wallet_id(&self) -> Option<&str> {
+        Some(&self.id)
+    }
+
+    fn redacted(&self) -> Self {
+        Self {
+            idempotency_key: redact(&self.idempotency_key),
+            ..self.clone()
+        }
+    }
+}
+
+impl Loggable for ListTransactionsRequest {
+    fn wallet_id(&self) -> Option<&str> {
+        Some(&self.wallet_id)
+    }
+
+    fn redacted(&self) -> Self {
+        self.clone()
+    }
+}
+
+impl Loggable for GetTrialBalanceRequest {
+    fn redacted(&self) -> Self {
+        self.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deposit_request() -> DepositRequest {
+        DepositRequest {
+            id: "01F8MECHZX3TBDSZ7XRADM79XE".to_string(),
+            amount: "100".to_string(),
+            etag: "".to_string(),
+            idempotency_key: "secret".to_string(),
+            memo: "rent for flat 4B".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_loggable_redacted() {
+        let redacted = deposit_request().redacted();
+        assert_eq!(redacted.id, "01F8MECHZX3TBDSZ7XRADM79XE");
+        assert_eq!(redacted.amount, "100");
+        assert_eq!(redacted.idempotency_key, REDACTED);
+        assert_eq!(redacted.memo, REDACTED);
+        assert!(!format!("{:?}", redacted).contains("flat 4B"));
+
+        // nothing there, nothing to hide
+        let redacted = DepositRequest {
+            memo: "".to_string(),
+            ..deposit_request()
+        }
+        .redacted();
+        assert_eq!(redacted.memo, "");
+    }
+
+    #[tokio::test]
+    async fn test_traced_request_id() {
+        let ok = |_: Request<DepositRequest>| async { Ok(Response::new(())) };
+        let metrics = Metrics::new();
+        let err = |_: Request<DepositRequest>| async {
+            Err::<Response<()>, _>(Status::not_found("wallet"))
+        };
+
+        // propagated
+        let mut request = Request::new(deposit_request());
+        request
+            .metadata_mut()
+            .insert(REQUEST_ID, MetadataValue::from_static("req-1"));
+        let response = traced(&metrics, "Deposit", request, ok).await.unwrap();
+        assert_eq!(response.metadata().get(REQUEST_ID).unwrap(), "req-1");
+
+        // generated, also for failures
+        let status = traced(&metrics, "Deposit", Request::new(deposit_request()), err)
+            .await
+            .unwrap_err();
+        let generated = status.metadata().get(REQUEST_ID).unwrap().to_str().unwrap();
+        assert!(generated.parse::<Ulid>().is_ok());
+
+        // replaced when unusable
+        let mut request = Request::new(deposit_request());
+        request.metadata_mut().insert(
+            REQUEST_ID,
+            MetadataValue::from_str(&"x".repeat(MAX_REQUEST_ID_LEN + 1)).unwrap(),
+        );
+        let response = traced(&metrics, "Deposit", request, ok).await.unwrap();
+        assert_eq!(
+            response.metadata().get(REQUEST_ID).unwrap().len(),
+            generated.len()
+        );
+    }
+}
@@ -1,3 +1,9 @@
+mod health;
+mod logging;
 mod service;
+mod tls;
 
+pub use self::health::Health;
+pub use self::logging::REQUEST_ID;
 pub use self::service::Service;
+pub use self::tls::{Tls, TlsError};
@@ -1,73 +1,243 @@
+use crate::grpc::health::Health;
+use crate::grpc::logging::traced;
+use crate::grpc::tls::Tls;
+use crate::metrics::Metrics;
 use anyhow::Result;
 use derive_new::new;
 use interface::controller::Controller;
+use interface::grpc_health_v1::health_server::HealthServer;
+use interface::idempotency::{Idempotency, IdempotencyRepository};
 use interface::osaifu_wallet_v1::wallet_service_server::{WalletService, WalletServiceServer};
 use interface::osaifu_wallet_v1::{CreateRequest, CreateResponse};
 use interface::osaifu_wallet_v1::{DeleteRequest, DeleteResponse};
+use interface::osaifu_wallet_v1::{DepositRequest, DepositResponse};
 use interface::osaifu_wallet_v1::{GetRequest, GetResponse};
+use interface::osaifu_wallet_v1::{GetTrialBalanceRequest, GetTrialBalanceResponse};
 use interface::osaifu_wallet_v1::{ListRequest, ListResponse};
+use interface::osaifu_wallet_v1::{ListTransactionsRequest, ListTransactionsResponse};
+use interface::osaifu_wallet_v1::{TransferRequest, TransferResponse};
+use interface::osaifu_wallet_v1::{UndeleteRequest, UndeleteResponse};
+use interface::osaifu_wallet_v1::{WithdrawRequest, WithdrawResponse};
+use std::future::Future;
 use std::net::SocketAddr;
+use tokio::net::TcpListener;
 use tonic::{transport::Server, Request, Response, Status};
 
+// the full gRPC method names, under which requests are logged and idempotency keys are kept
+const CREATE: &str = "/osaifu.wallet.v1.WalletService/Create";
+const DELETE: &str = "/osaifu.wallet.v1.WalletService/Delete";
+const DEPOSIT: &str = "/osaifu.wallet.v1.WalletService/Deposit";
+const GET: &str = "/osaifu.wallet.v1.WalletService/Get";
+const GET_TRIAL_BALANCE: &str = "/osaifu.wallet.v1.WalletService/GetTrialBalance";
+const LIST: &str = "/osaifu.wallet.v1.WalletService/List";
+const LIST_TRANSACTIONS: &str = "/osaifu.wallet.v1.WalletService/ListTransactions";
+const TRANSFER: &str = "/osaifu.wallet.v1.WalletService/Transfer";
+const UNDELETE: &str = "/osaifu.wallet.v1.WalletService/Undelete";
+const WITHDRAW: &str = "/osaifu.wallet.v1.WalletService/Withdraw";
+
 #[derive(new)]
-pub struct Service<C>
+pub struct Service<C, R>
 where
     C: Controller + std::marker::Sync + std::marker::Send,
+    R: IdempotencyRepository + std::marker::Sync + std::marker::Send,
 {
     controller: C,
+    idempotency: Idempotency<R>,
+    metrics: Metrics,
+    health: Health,
+    #[new(value = "true")]
+    reflection: bool,
+    #[new(default)]
+    tls: Option<Tls>,
 }
 
 #[tonic::async_trait]
-impl<C> WalletService for Service<C>
+impl<C, R> WalletService for Service<C, R>
 where
     C: Controller + std::marker::Sync + std::marker::Send + 'static,
+    R: IdempotencyRepository + std::marker::Sync + std::marker::Send + 'static,
 {
     async fn create(
         &self,
         request: Request<CreateRequest>,
     ) -> Result<Response<CreateResponse>, Status> {
-        println!("{:?}", request); // TODO: logger を実装して println! を削除する
-
-        self.controller.create(request)
+        traced(&self.metrics, CREATE, request, |request| async move {
+            let key = request.get_ref().idempotency_key.clone();
+            self.idempotency
+                .handle(CREATE, &key, request, |request| {
+                    self.controller.create(request)
+                })
+                .await
+        })
+        .await
     }
 
     async fn list(&self, request: Request<ListRequest>) -> Result<Response<ListResponse>, Status> {
-        println!("{:?}", request); // TODO: logger を実装して println! を削除する
-
-        self.controller.list(request)
+        traced(&self.metrics, LIST, request, |request| {
+            self.controller.list(request)
+        })
+        .await
     }
 
     async fn get(&self, request: Request<GetRequest>) -> Result<Response<GetResponse>, Status> {
-        println!("{:?}", request); // TODO: logger を実装して println! を削除する
-
-        self.controller.get(request)
+        traced(&self.metrics, GET, request, |request| {
+            self.controller.get(request)
+        })
+        .await
     }
 
     async fn delete(
         &self,
         request: Request<DeleteRequest>,
     ) -> Result<Response<DeleteResponse>, Status> {
-        println!("{:?}", request); // TODO: logger を実装して println! を削除する
+        traced(&self.metrics, DELETE, request, |request| async move {
+            let key = request.get_ref().idempotency_key.clone();
+            self.idempotency
+                .handle(DELETE, &key, request, |request| {
+                    self.controller.delete(request)
+                })
+                .await
+        })
+        .await
+    }
+
+    async fn deposit(
+        &self,
+        request: Request<DepositRequest>,
+    ) -> Result<Response<DepositResponse>, Status> {
+        traced(&self.metrics, DEPOSIT, request, |request| async move {
+            let key = request.get_ref().idempotency_key.clone();
+            self.idempotency
+                .handle(DEPOSIT, &key, request, |request| {
+                    self.controller.deposit(request)
+                })
+                .await
+        })
+        .await
+    }
+
+    async fn withdraw(
+        &self,
+        request: Request<WithdrawRequest>,
+    ) -> Result<Response<WithdrawResponse>, Status> {
+        traced(&self.metrics, WITHDRAW, request, |request| async move {
+            let key = request.get_ref().idempotency_key.clone();
+            self.idempotency
+                .handle(WITHDRAW, &key, request, |request| {
+                    self.controller.withdraw(request)
+                })
+                .await
+        })
+        .await
+    }
+
+    async fn transfer(
+        &self,
+        request: Request<TransferRequest>,
+    ) -> Result<Response<TransferResponse>, Status> {
+        traced(&self.metrics, TRANSFER, request, |request| async move {
+            let key = request.get_ref().idempotency_key.clone();
+            self.idempotency
+                .handle(TRANSFER, &key, request, |request| {
+                    self.controller.transfer(request)
+                })
+                .await
+        })
+        .await
+    }
 
-        self.controller.delete(request)
+    async fn undelete(
+        &self,
+        request: Request<UndeleteRequest>,
+    ) -> Result<Response<UndeleteResponse>, Status> {
+        traced(&self.metrics, UNDELETE, request, |request| async move {
+            let key = request.get_ref().idempotency_key.clone();
+            self.idempotency
+                .handle(UNDELETE, &key, request, |request| {
+                    self.controller.undelete(request)
+                })
+                .await
+        })
+        .await
+    }
+
+    async fn list_transactions(
+        &self,
+        request: Request<ListTransactionsRequest>,
+    ) -> Result<Response<ListTransactionsResponse>, Status> {
+        traced(&self.metrics, LIST_TRANSACTIONS, request, |request| {
+            self.controller.list_transactions(request)
+        })
+        .await
+    }
+
+    async fn get_trial_balance(
+        &self,
+        request: Request<GetTrialBalanceRequest>,
+    ) -> Result<Response<GetTrialBalanceResponse>, Status> {
+        traced(&self.metrics, GET_TRIAL_BALANCE, request, |request| {
+            self.controller.get_trial_balance(request)
+        })
+        .await
     }
 }
 
-impl<C> Service<C>
+impl<C, R> Service<C, R>
 where
     C: Controller + std::marker::Sync + std::marker::Send + 'static,
+    R: IdempotencyRepository + std::marker::Sync + std::marker::Send + 'static,
 {
-    pub async fn serve(self, addr: SocketAddr) -> Result<(), Box<dyn std::error::Error>> {
-        let reflection = tonic_reflection::server::Builder::configure()
-            .register_encoded_file_descriptor_set(interface::osaifu_wallet_v1::FILE_DESCRIPTOR_SET)
-            .build()
-            .unwrap();
+    /// Sets whether gRPC reflection is served next to the wallet service, which it is by default.
+    pub fn reflection(mut self, enabled: bool) -> Self {
+        self.reflection = enabled;
+        self
+    }
+
+    /// Serves over TLS rather than plaintext.
+    pub fn tls(mut self, tls: Tls) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+
+    /// Serves until `signal` completes, then stops accepting connections and waits for the
+    /// requests in flight to finish.
+    pub async fn serve_with_shutdown<F>(
+        mut self,
+        addr: SocketAddr,
+        signal: F,
+    ) -> Result<(), Box<dyn std::error::Error>>
+    where
+        F: Future<Output = ()>,
+    {
+        let reflection = match self.reflection {
+            true => Some(
+                tonic_reflection::server::Builder::configure()
+                    .register_encoded_file_descriptor_set(
+                        interface::osaifu_wallet_v1::FILE_DESCRIPTOR_SET,
+                    )
+                    .build()
+                    .unwrap(),
+            ),
+            false => None,
+        };
+
+        let health = HealthServer::new(self.health.clone());
+        let tls = self.tls.take();
 
-        Server::builder()
+        let router = Server::builder()
+            .add_service(health)
             .add_service(WalletServiceServer::new(self))
-            .add_service(reflection)
-            .serve(addr)
-            .await?;
+            .add_optional_service(reflection);
+        match tls {
+            // the handshakes are done apart from tonic, so that the certificate can be reloaded
+            Some(tls) => {
+                let listener = TcpListener::bind(addr).await?;
+                router
+                    .serve_with_incoming_shutdown(tls.incoming(listener), signal)
+                    .await?
+            }
+            None => router.serve_with_shutdown(addr, signal).await?,
+        }
 
         Ok(())
     }
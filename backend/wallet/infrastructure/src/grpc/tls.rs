@@ -0,0 +1,440 @@
+use crate::config::TlsConfig;
+use interface::controller::ClientIdentity;
+use rustls::internal::pemfile;
+use rustls::{
+    AllowAnyAuthenticatedClient, Certificate, NoClientAuth, PrivateKey, RootCertStore, ServerConfig,
+};
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+use thiserror::Error;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio_rustls::server::TlsStream;
+use tokio_rustls::TlsAcceptor;
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::{debug, warn};
+use x509_parser::extensions::GeneralName;
+
+// a client that has not finished the handshake by then is dropped, so it cannot hold a task
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+// accepting fails while the process is out of file descriptors; retrying at once would spin
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+// handshaken connections waiting for the server to take them
+const ACCEPT_BACKLOG: usize = 128;
+
+#[derive(Debug, Error)]
+pub enum TlsError {
+    #[error("failed to read {path}: {source}")]
+    Read { path: PathBuf, source: io::Error },
+    #[error("no certificate found in {0}")]
+    NoCertificate(PathBuf),
+    #[error("no private key found in {0}")]
+    NoKey(PathBuf),
+    #[error("invalid certificate or key: {0}")]
+    Rejected(#[from] rustls::TLSError),
+}
+
+/// The server's TLS settings, read from the files named in a [`TlsConfig`].
+///
+/// A client certificate signed by the configured CA is required when one is set. The files are
+/// read again by [`Tls::reload`] when they change, so a renewed certificate is served without a
+/// restart; connections already open keep the one they were made with. Clones share the
+/// settings, so the server and the task reloading them can each hold one.
+#[derive(Clone)]
+pub struct Tls {
+    config: TlsConfig,
+    loaded: Arc<RwLock<Loaded>>,
+}
+
+struct Loaded {
+    acceptor: TlsAcceptor,
+    // what the files looked like when read, to tell when they change
+    versions: Vec<Option<Version>>,
+}
+
+type Version = (SystemTime, u64);
+
+impl Tls {
+    pub fn load(config: &TlsConfig) -> Result<Self, TlsError> {
+        let loaded = Loaded::read(config)?;
+
+        Ok(Self {
+            config: config.clone(),
+            loaded: Arc::new(RwLock::new(loaded)),
+        })
+    }
+
+    /// Whether clients must present a certificate signed by the configured CA.
+    pub fn verifies_clients(&self) -> bool {
+        self.config.client_ca.is_some()
+    }
+
+    /// Reads the files again if any of them changed since they were last read, and returns
+    /// whether they were. The settings in use are kept when they cannot be read, as when a
+    /// certificate has been written but its key not yet.
+    pub fn reload(&self) -> Result<bool, TlsError> {
+        if self.loaded.read().unwrap().versions == versions(&self.config) {
+            return Ok(false);
+        }
+
+        let loaded = Loaded::read(&self.config)?;
+        *self.loaded.write().unwrap() = loaded;
+
+        Ok(true)
+    }
+
+    /// Accepts connections on `listener` and yields them once their handshake is done, which
+    /// happens in a task of its own so that a slow client holds up no one else. Connections
+    /// failing the handshake, like those without a valid client certificate when one is
+    /// required, are logged and dropped. It stops accepting when the stream is dropped.
+    pub fn incoming(
+        self,
+        listener: TcpListener,
+    ) -> ReceiverStream<Result<TlsStream<TcpStream>, io::Error>> {
+        let (sender, receiver) = mpsc::channel(ACCEPT_BACKLOG);
+
+        tokio::spawn(async move {
+            loop {
+                let (stream, remote_addr) = tokio::select! {
+                    accepted = listener.accept() => match accepted {
+                        Ok(accepted) => accepted,
+                        Err(e) => {
+                            warn!(error = %e, "failed to accept a connection");
+                            tokio::time::sleep(ACCEPT_BACKOFF).await;
+                            continue;
+                        }
+                    },
+                    // the server has stopped listening
+                    _ = sender.closed() => break,
+                };
+
+                let acceptor = self.acceptor();
+                let sender = sender.clone();
+                tokio::spawn(async move {
+                    match tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                        Ok(Ok(stream)) => {
+                            // the server may have stopped listening in the meantime
+                            let _ = sender.send(Ok(stream)).await;
+                        }
+                        Ok(Err(e)) => {
+                            debug!(%remote_addr, error = %e, "TLS handshake failed")
+                        }
+                        Err(_) => debug!(%remote_addr, "TLS handshake timed out"),
+                    }
+                });
+            }
+        });
+
+        ReceiverStream::new(receiver)
+    }
+
+    fn acceptor(&self) -> TlsAcceptor {
+        self.loaded.read().unwrap().acceptor.clone()
+    }
+}
+
+impl Loaded {
+    fn read(config: &TlsConfig) -> Result<Self, TlsError> {
+        // read first, so that a file changing while it is read is read again next time
+        let versions = versions(config);
+
+        let verifier = match &config.client_ca {
+            Some(path) => {
+                let mut roots = RootCertStore::empty();
+                for cert in read_certs(path)? {
+                    roots
+                        .add(&cert)
+                        .map_err(|e| TlsError::Rejected(rustls::TLSError::WebPKIError(e)))?;
+                }
+                AllowAnyAuthenticatedClient::new(roots)
+            }
+            None => NoClientAuth::new(),
+        };
+
+        let mut server_config = ServerConfig::new(verifier);
+        server_config.set_single_cert(read_certs(&config.cert)?, read_key(&config.key)?)?;
+        // gRPC runs over HTTP/2 only
+        server_config.set_protocols(&[b"h2".to_vec()]);
+
+        Ok(Self {
+            acceptor: TlsAcceptor::from(Arc::new(server_config)),
+            versions,
+        })
+    }
+}
+
+fn versions(config: &TlsConfig) -> Vec<Option<Version>> {
+    let files = [
+        Some(&config.cert),
+        Some(&config.key),
+        config.client_ca.as_ref(),
+    ];
+    files
+        .iter()
+        .map(|path| {
+            let metadata = std::fs::metadata(path.as_ref()?).ok()?;
+            Some((metadata.modified().ok()?, metadata.len()))
+        })
+        .collect()
+}
+
+fn open(path: &Path) -> Result<BufReader<File>, TlsError> {
+    File::open(path)
+        .map(BufReader::new)
+        .map_err(|source| TlsError::Read {
+            path: path.to_path_buf(),
+            source,
+        })
+}
+
+fn read_certs(path: &Path) -> Result<Vec<Certificate>, TlsError> {
+    match pemfile::certs(&mut open(path)?) {
+        Ok(certs) if !certs.is_empty() => Ok(certs),
+        _ => Err(TlsError::NoCertificate(path.to_path_buf())),
+    }
+}
+
+fn read_key(path: &Path) -> Result<PrivateKey, TlsError> {
+    let pkcs8 = pemfile::pkcs8_private_keys(&mut open(path)?).unwrap_or_default();
+    let keys = match pkcs8.is_empty() {
+        true => pemfile::rsa_private_keys(&mut open(path)?).unwrap_or_default(),
+        false => pkcs8,
+    };
+
+    keys.into_iter()
+        .next()
+        .ok_or_else(|| TlsError::NoKey(path.to_path_buf()))
+}
+
+/// Who a DER-encoded client certificate, already verified in the handshake, names.
+pub(crate) fn client_identity(der: &[u8]) -> Option<ClientIdentity> {
+    let (_, cert) = x509_parser::parse_x509_certificate(der).ok()?;
+
+    let common_name = cert
+        .subject()
+        .iter_common_name()
+        .next()
+        .and_then(|cn| cn.as_str().ok())
+        .map(str::to_string);
+    let alt_names = match cert.subject_alternative_name() {
+        Ok(Some(san)) => san
+            .value
+            .general_names
+            .iter()
+            .filter_map(|name| match name {
+                GeneralName::DNSName(name) | GeneralName::URI(name) => Some(name.to_string()),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    };
+    let fingerprint = Sha256::digest(der)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+
+    Some(ClientIdentity {
+        common_name,
+        alt_names,
+        fingerprint,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grpc::logging::traced;
+    use crate::metrics::Metrics;
+    use interface::osaifu_wallet_v1::GetRequest;
+    use rustls::{ClientConfig, Session};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio_rustls::webpki::DNSNameRef;
+    use tokio_rustls::TlsConnector;
+    use tokio_stream::StreamExt;
+    use tonic::transport::server::Connected;
+    use tonic::{Request, Response};
+
+    fn testdata(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("testdata/tls")
+            .join(name)
+    }
+
+    fn config(client_ca: bool) -> TlsConfig {
+        TlsConfig {
+            cert: testdata("server.pem"),
+            key: testdata("server.key"),
+            client_ca: client_ca.then(|| testdata("ca.pem")),
+        }
+    }
+
+    fn connector(client_cert: bool) -> TlsConnector {
+        let mut config = ClientConfig::new();
+        config
+            .root_store
+            .add_pem_file(&mut open(&testdata("ca.pem")).unwrap())
+            .unwrap();
+        if client_cert {
+            config
+                .set_single_client_cert(
+                    read_certs(&testdata("client.pem")).unwrap(),
+                    read_key(&testdata("client.key")).unwrap(),
+                )
+                .unwrap();
+        }
+        config.set_protocols(&[b"h2".to_vec()]);
+        TlsConnector::from(Arc::new(config))
+    }
+
+    async fn listen(
+        tls: Tls,
+    ) -> (
+        std::net::SocketAddr,
+        ReceiverStream<Result<TlsStream<TcpStream>, io::Error>>,
+    ) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        (addr, tls.incoming(listener))
+    }
+
+    async fn connect(
+        addr: std::net::SocketAddr,
+        client_cert: bool,
+    ) -> io::Result<tokio_rustls::client::TlsStream<TcpStream>> {
+        let stream = TcpStream::connect(addr).await?;
+        let domain = DNSNameRef::try_from_ascii_str("localhost").unwrap();
+        connector(client_cert).connect(domain, stream).await
+    }
+
+    #[test]
+    fn test_tls_load_err() {
+        // err: missing file
+        let mut config = config(true);
+        config.cert = testdata("missing.pem");
+        assert!(matches!(Tls::load(&config), Err(TlsError::Read { .. })));
+
+        // err: a key where the certificate should be
+        let mut config = self::config(true);
+        config.cert = testdata("server.key");
+        assert!(matches!(
+            Tls::load(&config),
+            Err(TlsError::NoCertificate(_))
+        ));
+
+        // err: a certificate where the key should be
+        let mut config = self::config(true);
+        config.key = testdata("server.pem");
+        assert!(matches!(Tls::load(&config), Err(TlsError::NoKey(_))));
+
+        // err: a key where the CA bundle should be
+        let mut config = self::config(true);
+        config.client_ca = Some(testdata("client.key"));
+        assert!(matches!(
+            Tls::load(&config),
+            Err(TlsError::NoCertificate(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_tls_incoming_mutual() {
+        let tls = Tls::load(&config(true)).unwrap();
+        assert!(tls.verifies_clients());
+        let (addr, mut incoming) = listen(tls).await;
+
+        let mut client = connect(addr, true).await.unwrap();
+        client.write_all(b"ping").await.unwrap();
+        let mut server = incoming.next().await.unwrap().unwrap();
+        let mut buffer = [0; 4];
+        server.read_exact(&mut buffer).await.unwrap();
+        assert_eq!(&buffer, b"ping");
+        assert_eq!(server.get_ref().1.get_alpn_protocol(), Some(&b"h2"[..]));
+
+        // the certificate the server verified is what tonic hands the service
+        let certs = server.connect_info().peer_certs().unwrap();
+        let identity = client_identity(certs[0].get_ref()).unwrap();
+        assert_eq!(identity.common_name.as_deref(), Some("gateway"));
+        assert_eq!(identity.alt_names, vec!["spiffe://osaifu/gateway"]);
+        assert_eq!(identity.fingerprint.len(), 64);
+        assert_eq!(identity.name(), "spiffe://osaifu/gateway");
+
+        // and what the service hands the controller and the idempotency keys
+        let mut request = Request::new(GetRequest::default());
+        request.extensions_mut().insert(server.connect_info());
+        let handled = traced(&Metrics::new(), "Get", request, |request| async move {
+            Ok(Response::new(ClientIdentity::of(&request).cloned()))
+        })
+        .await
+        .unwrap();
+        assert_eq!(handled.into_inner(), Some(identity));
+    }
+
+    #[tokio::test]
+    async fn test_tls_incoming_client_cert_required() {
+        let (addr, mut incoming) = listen(Tls::load(&config(true)).unwrap()).await;
+
+        // the handshake fails on the server, which closes the connection
+        let mut client = connect(addr, false).await.unwrap();
+        let _ = client.write_all(b"ping").await;
+        let mut buffer = [0; 4];
+        assert!(!matches!(client.read(&mut buffer).await, Ok(n) if n > 0));
+
+        // and a client presenting one is served next
+        connect(addr, true).await.unwrap();
+        let server = incoming.next().await.unwrap().unwrap();
+        assert!(server.connect_info().peer_certs().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_tls_incoming_server_only() {
+        let tls = Tls::load(&config(false)).unwrap();
+        assert!(!tls.verifies_clients());
+        let (addr, mut incoming) = listen(tls).await;
+
+        connect(addr, false).await.unwrap();
+        let server = incoming.next().await.unwrap().unwrap();
+        assert!(server.connect_info().peer_certs().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_tls_reload() {
+        let dir = std::env::temp_dir().join(format!("wallet-tls-{}", ulid::Ulid::new()));
+        std::fs::create_dir(&dir).unwrap();
+        let config = TlsConfig {
+            cert: dir.join("server.pem"),
+            key: dir.join("server.key"),
+            client_ca: None,
+        };
+        std::fs::copy(testdata("server.pem"), &config.cert).unwrap();
+        std::fs::copy(testdata("server.key"), &config.key).unwrap();
+
+        let tls = Tls::load(&config).unwrap();
+        assert!(!tls.reload().unwrap());
+        let (addr, _incoming) = listen(tls.clone()).await;
+
+        let served = |client: tokio_rustls::client::TlsStream<TcpStream>| {
+            let certs = client.get_ref().1.get_peer_certificates().unwrap();
+            client_identity(&certs[0].0).unwrap().common_name.unwrap()
+        };
+        assert_eq!(served(connect(addr, false).await.unwrap()), "wallet");
+
+        // err: the certificate is renewed, but not yet its key
+        std::fs::write(&config.key, "").unwrap();
+        assert!(matches!(tls.reload(), Err(TlsError::NoKey(_))));
+        assert_eq!(served(connect(addr, false).await.unwrap()), "wallet");
+
+        std::fs::copy(testdata("renewed.pem"), &config.cert).unwrap();
+        std::fs::copy(testdata("renewed.key"), &config.key).unwrap();
+        assert!(tls.reload().unwrap());
+        assert!(!tls.reload().unwrap());
+        assert_eq!(
+            served(connect(addr, false).await.unwrap()),
+            "wallet-renewed"
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
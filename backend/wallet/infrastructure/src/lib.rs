@@ -3,6 +3,12 @@ extern crate diesel;
 #[macro_use]
 extern crate diesel_migrations;
 
+pub mod broker;
+pub mod config;
 pub mod grpc;
+pub mod metrics;
+// diesel 1.4's table! and derives expand to impls inside functions, which the compiler now warns
+// about in every model
+#[allow(non_local_definitions)]
 pub mod postgres;
 pub mod ulid;
@@ -0,0 +1,4 @@
+mod pool;
+mod registry;
+
+pub use self::registry::*;
@@ -0,0 +1,303 @@
+use crate::metrics::pool::PoolCollector;
+use crate::postgres::DbPool;
+use domain::error::{Error, Result};
+use domain::event::{EventSubscriber, WalletEvent};
+use domain::vo::Money;
+use hyper::header::CONTENT_TYPE;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use prometheus::{
+    CounterVec, Encoder, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, Opts, Registry,
+    TextEncoder,
+};
+use rust_decimal::prelude::ToPrimitive;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tonic::Code;
+
+/// The path the metrics are served on.
+pub const METRICS_PATH: &str = "/metrics";
+
+/// The service's metrics, kept in one registry and served in the Prometheus text format.
+///
+/// Clones share the registry, so the gRPC service, the event dispatcher and the HTTP endpoint
+/// can each hold one.
+#[derive(Clone)]
+pub struct Metrics {
+    registry: Registry,
+    rpc_requests: IntCounterVec,
+    rpc_duration: HistogramVec,
+    wallets_created: IntCounter,
+    wallets_deleted: IntCounter,
+    wallets_undeleted: IntCounter,
+    volume: CounterVec,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new();
+
+        let rpc_requests = IntCounterVec::new(
+            Opts::new(
+                "wallet_rpc_requests_total",
+                "WalletService requests handled, by method and status code.",
+            ),
+            &["method", "code"],
+        )
+        .unwrap();
+        let rpc_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "wallet_rpc_duration_seconds",
+                "How long WalletService requests took, by method and status code.",
+            ),
+            &["method", "code"],
+        )
+        .unwrap();
+        let wallets_created =
+            IntCounter::new("wallet_wallets_created_total", "Wallets created.").unwrap();
+        let wallets_deleted =
+            IntCounter::new("wallet_wallets_deleted_total", "Wallets deleted.").unwrap();
+        let wallets_undeleted =
+            IntCounter::new("wallet_wallets_undeleted_total", "Wallets undeleted.").unwrap();
+        let volume = CounterVec::new(
+            Opts::new(
+                "wallet_volume_total",
+                "Money moved, in major units of the currency, by kind of transaction.",
+            ),
+            &["kind", "currency"],
+        )
+        .unwrap();
+
+        // the names are fixed and distinct, so registering them cannot fail
+        registry.register(Box::new(rpc_requests.clone())).unwrap();
+        registry.register(Box::new(rpc_duration.clone())).unwrap();
+        registry
+            .register(Box::new(wallets_created.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(wallets_deleted.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(wallets_undeleted.clone()))
+            .unwrap();
+        registry.register(Box::new(volume.clone())).unwrap();
+
+        Self {
+            registry,
+            rpc_requests,
+            rpc_duration,
+            wallets_created,
+            wallets_deleted,
+            wallets_undeleted,
+            volume,
+        }
+    }
+
+    /// Adds the connection pool's gauges and wait times, read from `connections` when scraped.
+    pub fn register_pool(&self, connections: DbPool) -> anyhow::Result<()> {
+        self.registry
+            .register(Box::new(PoolCollector::new(connections)))?;
+
+        Ok(())
+    }
+
+    /// Records a handled request under the last segment of its full gRPC method name.
+    pub(crate) fn observe_rpc(&self, method: &str, code: Code, latency: Duration) {
+        let method = method.rsplit('/').next().unwrap_or(method);
+        let code = format!("{:?}", code);
+        let labels = [method, code.as_str()];
+
+        self.rpc_requests.with_label_values(&labels).inc();
+        self.rpc_duration
+            .with_label_values(&labels)
+            .observe(latency.as_secs_f64());
+    }
+
+    pub async fn serve(
+        self,
+        addr: SocketAddr,
+    ) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let make_service = make_service_fn(move |_| {
+            let metrics = self.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    let response = metrics.respond(&request);
+                    async move { Ok::<_, Infallible>(response) }
+                }))
+            }
+        });
+
+        Server::bind(&addr).serve(make_service).await?;
+
+        Ok(())
+    }
+
+    fn respond(&self, request: &Request<Body>) -> Response<Body> {
+        if request.method() != Method::GET || request.uri().path() != METRICS_PATH {
+            return Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .body(Body::empty())
+                .unwrap();
+        }
+
+        let encoder = TextEncoder::new();
+        let mut buffer = vec![];
+        match encoder.encode(&self.registry.gather(), &mut buffer) {
+            Ok(_) => Response::builder()
+                .header(CONTENT_TYPE, encoder.format_type())
+                .body(Body::from(buffer))
+                .unwrap(),
+            Err(e) => Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(Body::from(e.to_string()))
+                .unwrap(),
+        }
+    }
+
+    fn observe_volume(&self, kind: &str, amount: &Money) {
+        // a float is close enough for a rate on a dashboard; the ledger has the exact amounts
+        let value = amount.amount().to_f64().unwrap_or_default();
+        self.volume
+            .with_label_values(&[kind, &amount.currency().to_string()])
+            .inc_by(value);
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventSubscriber<WalletEvent> for Metrics {
+    fn handle(&self, event: &WalletEvent) -> Result<(), Error> {
+        match event {
+            WalletEvent::Created { .. } => self.wallets_created.inc(),
+            WalletEvent::Deleted { .. } => self.wallets_deleted.inc(),
+            WalletEvent::Undeleted { .. } => self.wallets_undeleted.inc(),
+            WalletEvent::Deposited {
+                amount,
+                counterparty: None,
+                ..
+            } => self.observe_volume("deposit", amount),
+            WalletEvent::Withdrawn {
+                amount,
+                counterparty: None,
+                ..
+            } => self.observe_volume("withdrawal", amount),
+            WalletEvent::Withdrawn {
+                amount,
+                counterparty: Some(_),
+                ..
+            } => self.observe_volume("transfer", amount),
+            // counted once, when it left the other wallet
+            WalletEvent::Deposited {
+                counterparty: Some(_),
+                ..
+            } => {}
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use domain::entity::Wallet;
+    use domain::vo::{Currency, Id, Memo, Owner};
+    use ulid::Ulid;
+
+    fn new_id<T>() -> Id<T> {
+        Ulid::new().to_string().parse::<Id<T>>().unwrap()
+    }
+
+    async fn scrape(sut: &Metrics) -> String {
+        let request = Request::get(METRICS_PATH).body(Body::empty()).unwrap();
+        let response = sut.respond(&request);
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_metrics_observe_rpc() {
+        let sut = Metrics::new();
+        sut.observe_rpc(
+            "/osaifu.wallet.v1.WalletService/Deposit",
+            Code::Ok,
+            Duration::from_millis(5),
+        );
+        sut.observe_rpc(
+            "/osaifu.wallet.v1.WalletService/Deposit",
+            Code::NotFound,
+            Duration::from_millis(1),
+        );
+
+        let text = scrape(&sut).await;
+        assert!(text.contains(r#"wallet_rpc_requests_total{code="Ok",method="Deposit"} 1"#));
+        assert!(text.contains(r#"wallet_rpc_requests_total{code="NotFound",method="Deposit"} 1"#));
+        assert!(text.contains(r#"wallet_rpc_duration_seconds_count{code="Ok",method="Deposit"} 1"#));
+    }
+
+    #[tokio::test]
+    async fn test_metrics_handle() {
+        let sut = Metrics::new();
+        let id = new_id::<Wallet>();
+        let other = new_id::<Wallet>();
+        let amount = "12.34 USD".parse::<Money>().unwrap();
+        let events = vec![
+            WalletEvent::Created {
+                id: id.clone(),
+                owner: "alice".parse::<Owner>().unwrap(),
+                currency: Currency::USD,
+            },
+            WalletEvent::Deposited {
+                id: id.clone(),
+                transaction_id: new_id(),
+                amount,
+                counterparty: None,
+                memo: Memo::default(),
+            },
+            // a transfer is one withdrawal and one deposit, but moves the money only once
+            WalletEvent::Withdrawn {
+                id: id.clone(),
+                transaction_id: new_id(),
+                amount,
+                counterparty: Some(other.clone()),
+                memo: Memo::default(),
+            },
+            WalletEvent::Deposited {
+                id: other,
+                transaction_id: new_id(),
+                amount,
+                counterparty: Some(id.clone()),
+                memo: Memo::default(),
+            },
+            WalletEvent::Deleted { id },
+        ];
+        for event in &events {
+            sut.handle(event).unwrap();
+        }
+
+        let text = scrape(&sut).await;
+        assert!(text.contains("wallet_wallets_created_total 1"));
+        assert!(text.contains("wallet_wallets_deleted_total 1"));
+        assert!(text.contains("wallet_wallets_undeleted_total 0"));
+        assert!(text.contains(r#"wallet_volume_total{currency="USD",kind="deposit"} 12.34"#));
+        assert!(text.contains(r#"wallet_volume_total{currency="USD",kind="transfer"} 12.34"#));
+        assert!(!text.contains(r#"kind="withdrawal""#));
+    }
+
+    #[test]
+    fn test_metrics_respond_not_found() {
+        let sut = Metrics::new();
+        let request = Request::get("/").body(Body::empty()).unwrap();
+        assert_eq!(sut.respond(&request).status(), StatusCode::NOT_FOUND);
+
+        let request = Request::post(METRICS_PATH).body(Body::empty()).unwrap();
+        assert_eq!(sut.respond(&request).status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,38 @@
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use domain::error::Error;
+
+/// Classifies a database failure. Missing rows are left to callers, which know what they looked
+/// up, so a bare `NotFound` here means a query that had to return a row did not.
+pub(crate) fn database_error(e: DieselError) -> Error {
+    match e {
+        DieselError::DatabaseError(DatabaseErrorKind::SerializationFailure, _) => {
+            Error::conflict("SERIALIZATION_FAILURE", e)
+        }
+        DieselError::DatabaseError(DatabaseErrorKind::UnableToSendCommand, _) => {
+            Error::unavailable(e)
+        }
+        e => Error::internal(e),
+    }
+}
+
+/// Carries domain errors out of a diesel transaction, which requires its error type to be
+/// convertible from diesel's.
+pub(crate) struct TransactionError(Error);
+
+impl From<DieselError> for TransactionError {
+    fn from(e: DieselError) -> Self {
+        Self(database_error(e))
+    }
+}
+
+impl From<Error> for TransactionError {
+    fn from(e: Error) -> Self {
+        Self(e)
+    }
+}
+
+impl From<TransactionError> for Error {
+    fn from(e: TransactionError) -> Self {
+        e.0
+    }
+}
@@ -0,0 +1,125 @@
+use crate::postgres::error::database_error;
+use crate::postgres::models::*;
+use crate::postgres::schema::{journal_entries, ledger_accounts, postings, wallet_transactions};
+use chrono::{DateTime, Utc};
+use diesel::dsl::sql;
+use diesel::expression::SqlLiteral;
+use diesel::prelude::*;
+use diesel::sql_types::Text;
+use domain::entity::Transaction;
+use domain::error::{Error, Result};
+use domain::ledger::{Account, JournalEntry};
+use domain::vo::Currency;
+
+/// Balance of the wallet in the current row, as the credits less the debits of its account.
+pub(crate) fn wallet_balance() -> SqlLiteral<Text> {
+    sql::<Text>(
+        "(SELECT (- COALESCE(SUM(postings.amount::numeric), 0))::text FROM postings \
+         WHERE postings.account_id = 'wallet:' || wallets.id)",
+    )
+}
+
+pub(crate) fn open_account(
+    conn: &PgConnection,
+    account: &Account,
+    currency: Currency,
+) -> Result<(), Error> {
+    diesel::insert_into(ledger_accounts::table)
+        .values(&NewLedgerAccountModel {
+            id: account.to_string(),
+            kind: account.kind().to_string(),
+            currency: currency.to_string(),
+        })
+        .on_conflict_do_nothing()
+        .execute(conn)
+        .map_err(database_error)?;
+
+    Ok(())
+}
+
+/// Writes the entries with their postings. It must run inside the transaction that writes the
+/// change they record, which is also where the database checks that each entry balances.
+pub(crate) fn post(conn: &PgConnection, entries: Vec<JournalEntry>) -> Result<(), Error> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+
+    for entry in &entries {
+        for posting in entry.postings() {
+            open_account(conn, posting.account(), entry.currency())?;
+        }
+    }
+
+    diesel::insert_into(journal_entries::table)
+        .values(
+            entries
+                .iter()
+                .map(|e| NewJournalEntryModel {
+                    id: e.id().to_string(),
+                    memo: e.memo().to_string(),
+                    create_at: DateTime::<Utc>::from(*e.create_at()),
+                })
+                .collect::<Vec<_>>(),
+        )
+        .execute(conn)
+        .map_err(database_error)?;
+
+    diesel::insert_into(postings::table)
+        .values(
+            entries
+                .iter()
+                .flat_map(|e| {
+                    e.postings().iter().map(move |p| NewPostingModel {
+                        entry_id: e.id().to_string(),
+                        account_id: p.account().to_string(),
+                        currency: e.currency().to_string(),
+                        amount: p.signed_amount().amount().to_string(),
+                    })
+                })
+                .collect::<Vec<_>>(),
+        )
+        .execute(conn)
+        .map_err(database_error)?;
+
+    Ok(())
+}
+
+/// Writes the transactions that `ListTransactions` shows, in the transaction that posts their
+/// entries.
+pub(crate) fn record(conn: &PgConnection, transactions: Vec<Transaction>) -> Result<(), Error> {
+    if transactions.is_empty() {
+        return Ok(());
+    }
+
+    diesel::insert_into(wallet_transactions::table)
+        .values(
+            transactions
+                .iter()
+                .map(|t| NewTransactionModel {
+                    id: t.id().to_string(),
+                    wallet_id: t.wallet_id().to_string(),
+                    kind: t.kind().to_string(),
+                    amount: t.amount().amount().to_string(),
+                    balance: t.balance().amount().to_string(),
+                    currency: t.balance().currency().to_string(),
+                    counterparty: t.counterparty().as_ref().map(|c| c.to_string()),
+                    memo: t.memo().to_string(),
+                    create_at: DateTime::<Utc>::from(*t.create_at()),
+                })
+                .collect::<Vec<_>>(),
+        )
+        .execute(conn)
+        .map_err(database_error)?;
+
+    Ok(())
+}
+
+/// Drops the transactions of purged wallets. The journal keeps their entries, since the other
+/// side of each is still on the books. Called inside the transaction that purges them.
+pub(crate) fn forget(conn: &PgConnection, ids: &[String]) -> Result<(), Error> {
+    diesel::delete(wallet_transactions::table.filter(wallet_transactions::wallet_id.eq_any(ids)))
+        .execute(conn)
+        .map_err(database_error)?;
+
+    Ok(())
+}
@@ -1,11 +1,29 @@
+mod after_commit;
 mod connection;
+mod error;
+mod event_sourced_wallet;
+mod idempotency;
+mod ledger;
 mod models;
+mod outbox_relay;
+mod query_ledger;
+mod query_transaction;
 mod query_wallet;
 mod schema;
+mod unit_of_work;
 mod wallet;
+mod wallet_projector;
 
+pub use self::after_commit::*;
 pub use self::connection::*;
+pub use self::event_sourced_wallet::*;
+pub use self::idempotency::*;
 pub use self::models::*;
+pub use self::outbox_relay::*;
+pub use self::query_ledger::*;
+pub use self::query_transaction::*;
 pub use self::query_wallet::*;
 pub use self::schema::*;
+pub use self::unit_of_work::*;
 pub use self::wallet::*;
+pub use self::wallet_projector::*;
@@ -1,10 +1,46 @@
+use crate::postgres::schema::events;
+use crate::postgres::schema::idempotency_keys;
+use crate::postgres::schema::journal_entries;
+use crate::postgres::schema::ledger_accounts;
+use crate::postgres::schema::outbox;
+use crate::postgres::schema::postings;
+use crate::postgres::schema::wallet_transactions;
+use crate::postgres::schema::wallet_views;
 use crate::postgres::schema::wallets;
+use chrono::{DateTime, Utc};
+use diesel::sql_types::{Text, Varchar};
 
 #[derive(Queryable, Debug)]
 pub struct WalletModel {
     pub id: String,
     pub owner: String,
     pub balance: String,
+    pub currency: String,
+    pub version: i64,
+}
+
+#[derive(Queryable, Debug)]
+pub struct WalletViewModel {
+    pub id: String,
+    pub owner: String,
+    pub currency: String,
+    pub balance: String,
+    pub status: String,
+    pub version: i64,
+    pub transaction_count: i64,
+    pub create_at: DateTime<Utc>,
+    pub last_activity_at: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[table_name = "wallet_views"]
+pub struct NewWalletViewModel {
+    pub id: String,
+    pub owner: String,
+    pub currency: String,
+    pub version: i64,
+    pub create_at: DateTime<Utc>,
+    pub last_activity_at: DateTime<Utc>,
 }
 
 #[derive(Insertable)]
@@ -12,5 +48,136 @@ pub struct WalletModel {
 pub struct NewWalletModel {
     pub id: String,
     pub owner: String,
+    pub currency: String,
+    pub version: i64,
+}
+
+#[derive(Queryable, Debug)]
+pub struct IdempotencyKeyModel {
+    pub request_hash: String,
+    pub response: Option<Vec<u8>>,
+}
+
+#[derive(Insertable)]
+#[table_name = "idempotency_keys"]
+pub struct NewIdempotencyKeyModel {
+    pub key: String,
+    pub request_hash: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Queryable, Debug)]
+pub struct TransactionModel {
+    pub id: String,
+    pub wallet_id: String,
+    pub kind: String,
+    pub amount: String,
     pub balance: String,
+    pub currency: String,
+    pub counterparty: Option<String>,
+    pub memo: String,
+    pub create_at: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[table_name = "wallet_transactions"]
+pub struct NewTransactionModel {
+    pub id: String,
+    pub wallet_id: String,
+    pub kind: String,
+    pub amount: String,
+    pub balance: String,
+    pub currency: String,
+    pub counterparty: Option<String>,
+    pub memo: String,
+    pub create_at: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[table_name = "ledger_accounts"]
+pub struct NewLedgerAccountModel {
+    pub id: String,
+    pub kind: String,
+    pub currency: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "journal_entries"]
+pub struct NewJournalEntryModel {
+    pub id: String,
+    pub memo: String,
+    pub create_at: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[table_name = "postings"]
+pub struct NewPostingModel {
+    pub entry_id: String,
+    pub account_id: String,
+    pub currency: String,
+    pub amount: String,
+}
+
+#[derive(QueryableByName, Debug)]
+pub struct TrialBalanceLineModel {
+    #[sql_type = "Varchar"]
+    pub account_id: String,
+    #[sql_type = "Varchar"]
+    pub currency: String,
+    #[sql_type = "Text"]
+    pub debit: String,
+    #[sql_type = "Text"]
+    pub credit: String,
+}
+
+#[derive(Queryable, Debug)]
+pub struct EventModel {
+    pub sequence: i64,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    pub create_at: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[table_name = "events"]
+pub struct NewEventModel {
+    pub stream_id: String,
+    pub sequence: i64,
+    pub stream_type: String,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    // now unless the event is imported from what happened before
+    pub create_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Queryable, Debug)]
+pub struct OutboxModel {
+    pub id: i64,
+    pub aggregate_type: String,
+    pub aggregate_id: String,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    pub create_at: DateTime<Utc>,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[table_name = "outbox"]
+pub struct NewOutboxModel {
+    pub aggregate_type: String,
+    pub aggregate_id: String,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    pub version: i64,
+}
+
+#[derive(Queryable, Debug)]
+pub struct ProjectionModel {
+    pub id: i64,
+    pub aggregate_id: String,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    pub create_at: DateTime<Utc>,
+    pub version: i64,
 }
@@ -2,9 +2,115 @@ table! {
     wallets (id) {
         id -> Varchar,
         owner -> Varchar,
-        balance -> Text,
         create_at -> Timestamptz,
         update_at -> Timestamptz,
         delete_at -> Nullable<Timestamptz>,
+        currency -> Varchar,
+        version -> Int8,
+    }
+}
+
+table! {
+    idempotency_keys (key) {
+        key -> Varchar,
+        request_hash -> Varchar,
+        response -> Nullable<Bytea>,
+        create_at -> Timestamptz,
+        expires_at -> Timestamptz,
+    }
+}
+
+table! {
+    wallet_transactions (id) {
+        id -> Varchar,
+        wallet_id -> Varchar,
+        kind -> Varchar,
+        amount -> Text,
+        balance -> Text,
+        currency -> Varchar,
+        counterparty -> Nullable<Varchar>,
+        memo -> Text,
+        create_at -> Timestamptz,
+    }
+}
+
+table! {
+    ledger_accounts (id) {
+        id -> Varchar,
+        kind -> Varchar,
+        currency -> Varchar,
+        create_at -> Timestamptz,
+    }
+}
+
+table! {
+    journal_entries (id) {
+        id -> Varchar,
+        memo -> Text,
+        create_at -> Timestamptz,
+    }
+}
+
+table! {
+    postings (id) {
+        id -> Int8,
+        entry_id -> Varchar,
+        account_id -> Varchar,
+        currency -> Varchar,
+        amount -> Text,
+    }
+}
+
+table! {
+    events (stream_id, sequence) {
+        stream_id -> Varchar,
+        sequence -> Int8,
+        stream_type -> Varchar,
+        kind -> Varchar,
+        payload -> Jsonb,
+        create_at -> Timestamptz,
+    }
+}
+
+table! {
+    outbox (id) {
+        id -> Int8,
+        aggregate_type -> Varchar,
+        aggregate_id -> Varchar,
+        kind -> Varchar,
+        payload -> Jsonb,
+        create_at -> Timestamptz,
+        attempts -> Int4,
+        next_attempt_at -> Timestamptz,
+        last_error -> Nullable<Text>,
+        sent_at -> Nullable<Timestamptz>,
+        version -> Int8,
+        projected_at -> Nullable<Timestamptz>,
+    }
+}
+
+table! {
+    wallet_views (id) {
+        id -> Varchar,
+        owner -> Varchar,
+        currency -> Varchar,
+        balance -> Text,
+        status -> Varchar,
+        version -> Int8,
+        transaction_count -> Int8,
+        create_at -> Timestamptz,
+        last_activity_at -> Timestamptz,
     }
 }
+
+joinable!(postings -> journal_entries (entry_id));
+
+allow_tables_to_appear_in_same_query!(
+    events,
+    journal_entries,
+    ledger_accounts,
+    outbox,
+    postings,
+    wallet_views,
+    wallets
+);
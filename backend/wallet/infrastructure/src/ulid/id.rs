@@ -1,14 +1,18 @@
-use anyhow::{Error, Result};
+use domain::error::{Error, Result};
 use domain::repository::IdRepository as Repository;
 use domain::vo::Id;
 use ulid::Ulid;
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct IdRepository {}
 
 impl Repository for IdRepository {
     fn generate<T>(&self) -> Result<Id<T>, Error> {
-        Ulid::new().to_string().as_str().parse::<Id<T>>()
+        Ulid::new()
+            .to_string()
+            .as_str()
+            .parse::<Id<T>>()
+            .map_err(Error::internal)
     }
 }
 
@@ -18,12 +22,19 @@ mod tests {
     use domain::aggregate::AggregateRoot;
     use domain::entity::Entity;
     use domain::vo::Id;
+    use std::time::{Duration, SystemTime};
 
     #[derive(Default, Clone, Debug, PartialEq, Eq)]
     struct DummyEntity {
         id: Id<DummyEntity>,
     }
-    impl AggregateRoot for DummyEntity {}
+    impl AggregateRoot for DummyEntity {
+        type Event = ();
+
+        fn take_events(&mut self) -> Vec<Self::Event> {
+            vec![]
+        }
+    }
     impl Entity for DummyEntity {
         type Id = Id<DummyEntity>;
 
@@ -40,4 +51,16 @@ mod tests {
 
         assert!(sut.generate::<DummyEntity>().is_ok());
     }
+
+    #[test]
+    fn test_id_repository_timestamp() {
+        let sut = IdRepository::default();
+
+        let before = SystemTime::now() - Duration::from_millis(1);
+        let id = sut.generate::<DummyEntity>().unwrap();
+        let after = SystemTime::now();
+
+        assert!(before <= id.created_at() && id.created_at() <= after);
+        assert_eq!(id.to_string().parse::<Id<DummyEntity>>().unwrap(), id);
+    }
 }
@@ -11,5 +11,14 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             &["../../../api/osaifu/wallet/v1/wallet.proto"],
             &["../../../api", "../../../third_party/googleapis"], // specify the root location to search proto dependencies
         )?;
+    // the standard health checking protocol, served next to the wallet service
+    tonic_build::configure()
+        .build_server(true)
+        .build_client(true)
+        .format(true)
+        .compile(
+            &["../../../third_party/grpc/health/v1/health.proto"],
+            &["../../../third_party"],
+        )?;
     Ok(())
 }
@@ -1,100 +1,463 @@
+use crate::controller::status::to_status;
+use crate::osaifu_wallet_v1::LedgerAccount as PBLedgerAccount;
+use crate::osaifu_wallet_v1::Transaction as PBTransaction;
+use crate::osaifu_wallet_v1::TrialBalanceTotal as PBTrialBalanceTotal;
 use crate::osaifu_wallet_v1::Wallet as PBWallet;
 use crate::osaifu_wallet_v1::{CreateRequest, CreateResponse};
 use crate::osaifu_wallet_v1::{DeleteRequest, DeleteResponse};
+use crate::osaifu_wallet_v1::{DepositRequest, DepositResponse};
 use crate::osaifu_wallet_v1::{GetRequest, GetResponse};
+use crate::osaifu_wallet_v1::{GetTrialBalanceRequest, GetTrialBalanceResponse};
 use crate::osaifu_wallet_v1::{ListRequest, ListResponse};
-use anyhow::Result;
+use crate::osaifu_wallet_v1::{ListTransactionsRequest, ListTransactionsResponse};
+use crate::osaifu_wallet_v1::{TransferRequest, TransferResponse};
+use crate::osaifu_wallet_v1::{UndeleteRequest, UndeleteResponse};
+use crate::osaifu_wallet_v1::{WithdrawRequest, WithdrawResponse};
+use async_trait::async_trait;
 use derive_new::new;
-use query::port::{ListWalletsInputData, ListWalletsOutputData, QueryPort};
+use domain::entity::Wallet;
+use domain::error::Error;
+use query::port::{
+    GetTrialBalanceInputData, GetTrialBalanceOutputData, ListTransactionsInputData,
+    ListTransactionsOutputData, ListWalletsInputData, ListWalletsOutputData, QueryPort,
+};
+use query::view::Wallet as WalletView;
+use std::convert::TryFrom;
+use std::time::SystemTime;
 use tonic::{Request, Response, Status};
 use usecase::port::{
     CreateWalletInputData, CreateWalletOutputData, DeleteWalletInputData, DeleteWalletOutputData,
-    GetWalletInputData, GetWalletOutputData, Port,
+    DepositWalletInputData, DepositWalletOutputData, GetWalletInputData, GetWalletOutputData, Port,
+    TransferInputData, TransferOutputData, UndeleteWalletInputData, UndeleteWalletOutputData,
+    WithdrawWalletInputData, WithdrawWalletOutputData,
 };
 
-pub trait Controller {
-    fn create(&self, request: Request<CreateRequest>) -> Result<Response<CreateResponse>, Status>;
-    fn list(&self, request: Request<ListRequest>) -> Result<Response<ListResponse>, Status>;
-    fn get(&self, request: Request<GetRequest>) -> Result<Response<GetResponse>, Status>;
-    fn delete(&self, request: Request<DeleteRequest>) -> Result<Response<DeleteResponse>, Status>;
+const STATUS_ACTIVE: &str = "ACTIVE";
+const STATUS_DELETED: &str = "DELETED";
+
+#[async_trait]
+pub trait Controller: Send + Sync {
+    async fn create(
+        &self,
+        request: Request<CreateRequest>,
+    ) -> Result<Response<CreateResponse>, Status>;
+    async fn list(&self, request: Request<ListRequest>) -> Result<Response<ListResponse>, Status>;
+    async fn get(&self, request: Request<GetRequest>) -> Result<Response<GetResponse>, Status>;
+    async fn delete(
+        &self,
+        request: Request<DeleteRequest>,
+    ) -> Result<Response<DeleteResponse>, Status>;
+    async fn deposit(
+        &self,
+        request: Request<DepositRequest>,
+    ) -> Result<Response<DepositResponse>, Status>;
+    async fn withdraw(
+        &self,
+        request: Request<WithdrawRequest>,
+    ) -> Result<Response<WithdrawResponse>, Status>;
+    async fn transfer(
+        &self,
+        request: Request<TransferRequest>,
+    ) -> Result<Response<TransferResponse>, Status>;
+    async fn undelete(
+        &self,
+        request: Request<UndeleteRequest>,
+    ) -> Result<Response<UndeleteResponse>, Status>;
+    async fn list_transactions(
+        &self,
+        request: Request<ListTransactionsRequest>,
+    ) -> Result<Response<ListTransactionsResponse>, Status>;
+    async fn get_trial_balance(
+        &self,
+        request: Request<GetTrialBalanceRequest>,
+    ) -> Result<Response<GetTrialBalanceResponse>, Status>;
+}
+
+// etags are the quoted wallet version, so a client can echo back what it read (weak etags
+// included) and the version check happens in the usecase
+fn to_etag(version: impl std::fmt::Display) -> String {
+    format!("\"{}\"", version)
+}
+
+fn from_etag(etag: &str) -> String {
+    etag.trim_start_matches("W/").trim_matches('"').to_string()
+}
+
+fn from_timestamp(
+    field: &'static str,
+    timestamp: Option<prost_types::Timestamp>,
+) -> Result<Option<SystemTime>, Error> {
+    timestamp
+        .map(|t| {
+            SystemTime::try_from(t)
+                .map_err(|_| Error::invalid_argument(field, "timestamp is out of range"))
+        })
+        .transpose()
+}
+
+// every wallet message is built here, from the aggregate the commands return or from the read
+// model List reads; only the latter knows how active a wallet is, which List adds afterwards
+fn to_wallet(
+    id: String,
+    owner: String,
+    balance: String,
+    currency: String,
+    version: impl std::fmt::Display,
+    status: &str,
+) -> PBWallet {
+    PBWallet {
+        id,
+        owner,
+        balance,
+        currency,
+        deleted: status == STATUS_DELETED,
+        etag: to_etag(version),
+        status: status.to_string(),
+        transaction_count: 0,
+        last_activity_time: None,
+    }
+}
+
+// the commands never hand back a wallet waiting to be purged
+fn from_aggregate(wallet: &Wallet) -> PBWallet {
+    to_wallet(
+        wallet.id().to_string(),
+        wallet.owner().to_string(),
+        wallet.balance().amount().to_string(),
+        wallet.balance().currency().to_string(),
+        wallet.version(),
+        STATUS_ACTIVE,
+    )
+}
+
+fn from_view(view: WalletView) -> PBWallet {
+    PBWallet {
+        transaction_count: view.transaction_count,
+        last_activity_time: Some(view.last_activity_at.into()),
+        ..to_wallet(
+            view.id,
+            view.owner,
+            view.balance,
+            view.currency,
+            view.version,
+            &view.status,
+        )
+    }
+}
+
+/// The use cases a `WalletController` hands its requests to, one for each RPC.
+pub struct WalletPorts<
+    Create,
+    List,
+    Get,
+    Delete,
+    Deposit,
+    Withdraw,
+    Transfer,
+    Undelete,
+    ListTransactions,
+    GetTrialBalance,
+> {
+    pub create_wallet: Create,
+    pub list_wallets: List,
+    pub get_wallet: Get,
+    pub delete_wallet: Delete,
+    pub deposit_wallet: Deposit,
+    pub withdraw_wallet: Withdraw,
+    pub transfer: Transfer,
+    pub undelete_wallet: Undelete,
+    pub list_transactions: ListTransactions,
+    pub get_trial_balance: GetTrialBalance,
 }
 
 #[derive(new)]
-pub struct WalletController<Create, List, Get, Delete>
-where
+pub struct WalletController<
+    Create,
+    List,
+    Get,
+    Delete,
+    Deposit,
+    Withdraw,
+    Transfer,
+    Undelete,
+    ListTransactions,
+    GetTrialBalance,
+> where
     Create: Port<CreateWalletInputData, CreateWalletOutputData>,
     List: QueryPort<ListWalletsInputData, ListWalletsOutputData>,
     Get: Port<GetWalletInputData, GetWalletOutputData>,
     Delete: Port<DeleteWalletInputData, DeleteWalletOutputData>,
+    Deposit: Port<DepositWalletInputData, DepositWalletOutputData>,
+    Withdraw: Port<WithdrawWalletInputData, WithdrawWalletOutputData>,
+    Transfer: Port<TransferInputData, TransferOutputData>,
+    Undelete: Port<UndeleteWalletInputData, UndeleteWalletOutputData>,
+    ListTransactions: QueryPort<ListTransactionsInputData, ListTransactionsOutputData>,
+    GetTrialBalance: QueryPort<GetTrialBalanceInputData, GetTrialBalanceOutputData>,
 {
-    create_wallet: Create,
-    list_wallets: List,
-    get_wallet: Get,
-    delete_wallet: Delete,
+    ports: WalletPorts<
+        Create,
+        List,
+        Get,
+        Delete,
+        Deposit,
+        Withdraw,
+        Transfer,
+        Undelete,
+        ListTransactions,
+        GetTrialBalance,
+    >,
 }
 
-impl<Create, List, Get, Delete> Controller for WalletController<Create, List, Get, Delete>
+#[async_trait]
+impl<
+        Create,
+        List,
+        Get,
+        Delete,
+        Deposit,
+        Withdraw,
+        Transfer,
+        Undelete,
+        ListTransactions,
+        GetTrialBalance,
+    > Controller
+    for WalletController<
+        Create,
+        List,
+        Get,
+        Delete,
+        Deposit,
+        Withdraw,
+        Transfer,
+        Undelete,
+        ListTransactions,
+        GetTrialBalance,
+    >
 where
     Create: Port<CreateWalletInputData, CreateWalletOutputData>,
     List: QueryPort<ListWalletsInputData, ListWalletsOutputData>,
     Get: Port<GetWalletInputData, GetWalletOutputData>,
     Delete: Port<DeleteWalletInputData, DeleteWalletOutputData>,
+    Deposit: Port<DepositWalletInputData, DepositWalletOutputData>,
+    Withdraw: Port<WithdrawWalletInputData, WithdrawWalletOutputData>,
+    Transfer: Port<TransferInputData, TransferOutputData>,
+    Undelete: Port<UndeleteWalletInputData, UndeleteWalletOutputData>,
+    ListTransactions: QueryPort<ListTransactionsInputData, ListTransactionsOutputData>,
+    GetTrialBalance: QueryPort<GetTrialBalanceInputData, GetTrialBalanceOutputData>,
 {
-    fn create(&self, request: Request<CreateRequest>) -> Result<Response<CreateResponse>, Status> {
-        let input = CreateWalletInputData::new(request.get_ref().owner.to_string());
+    async fn create(
+        &self,
+        request: Request<CreateRequest>,
+    ) -> Result<Response<CreateResponse>, Status> {
+        let currency = match request.get_ref().currency.as_str() {
+            "" => "JPY",
+            currency => currency,
+        };
+        let input =
+            CreateWalletInputData::new(request.get_ref().owner.to_string(), currency.to_string());
 
-        match self.create_wallet.handle(input) {
+        match self.ports.create_wallet.handle(input).await {
             Ok(output) => Ok(Response::new(CreateResponse {
-                wallet: Some(PBWallet {
-                    id: output.wallet.id().to_string(),
-                    owner: "alice".to_string(),
-                    balance: output.wallet.balance().to_string(),
-                }),
+                wallet: Some(from_aggregate(&output.wallet)),
             })),
-            Err(_) => Err(Status::internal("error")),
+            Err(e) => Err(to_status(e)),
         }
     }
 
-    fn list(&self, _request: Request<ListRequest>) -> Result<Response<ListResponse>, Status> {
-        let input = ListWalletsInputData::new("".to_string());
+    async fn list(&self, request: Request<ListRequest>) -> Result<Response<ListResponse>, Status> {
+        let input = ListWalletsInputData::new(
+            request.get_ref().owner.to_string(),
+            request.get_ref().page_size,
+            request.get_ref().page_token.to_string(),
+            request.get_ref().show_deleted,
+        );
 
-        match self.list_wallets.handle(input) {
+        match self.ports.list_wallets.handle(input).await {
             Ok(output) => Ok(Response::new(ListResponse {
-                wallets: output
-                    .wallets
-                    .iter()
-                    .map(|x| PBWallet {
-                        id: x.id.clone(),
-                        owner: x.owner.clone(),
-                        balance: x.balance.clone(),
-                    })
-                    .collect(),
+                wallets: output.wallets.into_iter().map(from_view).collect(),
+                next_page_token: output.next_page_token,
+                total_size: output.total_size,
             })),
-            Err(_) => Err(Status::internal("error")),
+            Err(e) => Err(to_status(e)),
         }
     }
 
-    fn get(&self, request: Request<GetRequest>) -> Result<Response<GetResponse>, Status> {
+    async fn get(&self, request: Request<GetRequest>) -> Result<Response<GetResponse>, Status> {
         let input = GetWalletInputData::new(request.get_ref().id.to_string());
 
-        match self.get_wallet.handle(input) {
+        match self.ports.get_wallet.handle(input).await {
             Ok(output) => Ok(Response::new(GetResponse {
-                wallet: Some(PBWallet {
-                    id: output.wallet.id().to_string(),
-                    owner: "alice".to_string(),
-                    balance: output.wallet.balance().to_string(),
-                }),
+                wallet: Some(from_aggregate(&output.wallet)),
             })),
-            Err(_) => Err(Status::internal("error")),
+            Err(e) => Err(to_status(e)),
         }
     }
 
-    fn delete(&self, request: Request<DeleteRequest>) -> Result<Response<DeleteResponse>, Status> {
-        let input = DeleteWalletInputData::new(request.get_ref().id.to_string());
+    async fn delete(
+        &self,
+        request: Request<DeleteRequest>,
+    ) -> Result<Response<DeleteResponse>, Status> {
+        let input = DeleteWalletInputData::new(
+            request.get_ref().id.to_string(),
+            from_etag(&request.get_ref().etag),
+        );
 
-        match self.delete_wallet.handle(input) {
+        match self.ports.delete_wallet.handle(input).await {
             Ok(_) => Ok(Response::new(DeleteResponse {})),
-            Err(_) => Err(Status::internal("error")),
+            Err(e) => Err(to_status(e)),
+        }
+    }
+
+    async fn deposit(
+        &self,
+        request: Request<DepositRequest>,
+    ) -> Result<Response<DepositResponse>, Status> {
+        let input = DepositWalletInputData::new(
+            request.get_ref().id.to_string(),
+            request.get_ref().amount.to_string(),
+            from_etag(&request.get_ref().etag),
+            request.get_ref().memo.to_string(),
+        );
+
+        match self.ports.deposit_wallet.handle(input).await {
+            Ok(output) => Ok(Response::new(DepositResponse {
+                wallet: Some(from_aggregate(&output.wallet)),
+            })),
+            Err(e) => Err(to_status(e)),
+        }
+    }
+
+    async fn withdraw(
+        &self,
+        request: Request<WithdrawRequest>,
+    ) -> Result<Response<WithdrawResponse>, Status> {
+        let input = WithdrawWalletInputData::new(
+            request.get_ref().id.to_string(),
+            request.get_ref().amount.to_string(),
+            from_etag(&request.get_ref().etag),
+            request.get_ref().memo.to_string(),
+        );
+
+        match self.ports.withdraw_wallet.handle(input).await {
+            Ok(output) => Ok(Response::new(WithdrawResponse {
+                wallet: Some(from_aggregate(&output.wallet)),
+            })),
+            Err(e) => Err(to_status(e)),
+        }
+    }
+
+    async fn transfer(
+        &self,
+        request: Request<TransferRequest>,
+    ) -> Result<Response<TransferResponse>, Status> {
+        let input = TransferInputData::new(
+            request.get_ref().from_id.to_string(),
+            request.get_ref().to_id.to_string(),
+            request.get_ref().amount.to_string(),
+            from_etag(&request.get_ref().from_etag),
+            from_etag(&request.get_ref().to_etag),
+            request.get_ref().memo.to_string(),
+        );
+
+        match self.ports.transfer.handle(input).await {
+            Ok(output) => Ok(Response::new(TransferResponse {
+                from: Some(from_aggregate(&output.from)),
+                to: Some(from_aggregate(&output.to)),
+            })),
+            Err(e) => Err(to_status(e)),
+        }
+    }
+
+    async fn undelete(
+        &self,
+        request: Request<UndeleteRequest>,
+    ) -> Result<Response<UndeleteResponse>, Status> {
+        let input = UndeleteWalletInputData::new(request.get_ref().id.to_string());
+
+        match self.ports.undelete_wallet.handle(input).await {
+            Ok(output) => Ok(Response::new(UndeleteResponse {
+                wallet: Some(from_aggregate(&output.wallet)),
+            })),
+            Err(e) => Err(to_status(e)),
+        }
+    }
+
+    async fn list_transactions(
+        &self,
+        request: Request<ListTransactionsRequest>,
+    ) -> Result<Response<ListTransactionsResponse>, Status> {
+        let times = from_timestamp("start_time", request.get_ref().start_time.clone()).and_then(
+            |start_time| {
+                let end_time = from_timestamp("end_time", request.get_ref().end_time.clone())?;
+                Ok((start_time, end_time))
+            },
+        );
+        let (start_time, end_time) = times.map_err(to_status)?;
+        let input = ListTransactionsInputData::new(
+            request.get_ref().wallet_id.to_string(),
+            request.get_ref().page_size,
+            request.get_ref().page_token.to_string(),
+            start_time,
+            end_time,
+        );
+
+        match self.ports.list_transactions.handle(input).await {
+            Ok(output) => Ok(Response::new(ListTransactionsResponse {
+                transactions: output
+                    .transactions
+                    .into_iter()
+                    .map(|x| PBTransaction {
+                        id: x.id,
+                        wallet_id: x.wallet_id,
+                        kind: x.kind,
+                        amount: x.amount,
+                        balance: x.balance,
+                        currency: x.currency,
+                        counterparty: x.counterparty.unwrap_or_default(),
+                        memo: x.memo,
+                        create_time: Some(x.create_at.into()),
+                    })
+                    .collect(),
+                next_page_token: output.next_page_token,
+                total_size: output.total_size,
+            })),
+            Err(e) => Err(to_status(e)),
+        }
+    }
+
+    async fn get_trial_balance(
+        &self,
+        request: Request<GetTrialBalanceRequest>,
+    ) -> Result<Response<GetTrialBalanceResponse>, Status> {
+        let input = GetTrialBalanceInputData::new(request.get_ref().currency.to_string());
+
+        match self.ports.get_trial_balance.handle(input).await {
+            Ok(output) => Ok(Response::new(GetTrialBalanceResponse {
+                accounts: output
+                    .accounts
+                    .into_iter()
+                    .map(|x| PBLedgerAccount {
+                        account: x.account,
+                        kind: x.kind,
+                        currency: x.currency,
+                        debit: x.debit,
+                        credit: x.credit,
+                        balance: x.balance,
+                    })
+                    .collect(),
+                totals: output
+                    .totals
+                    .into_iter()
+                    .map(|x| PBTrialBalanceTotal {
+                        currency: x.currency,
+                        debit: x.debit,
+                        credit: x.credit,
+                        balanced: x.balanced,
+                    })
+                    .collect(),
+                balanced: output.balanced,
+            })),
+            Err(e) => Err(to_status(e)),
         }
     }
 }
@@ -102,141 +465,730 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use anyhow::bail;
+    use anyhow::anyhow;
     use domain::entity::*;
+    use domain::error::Error;
+    use domain::repository::ConcurrencyError;
     use domain::vo::*;
     use query::port::*;
     use usecase::port::*;
 
+    type MockPorts = WalletPorts<
+        MockPort<CreateWalletInputData, CreateWalletOutputData>,
+        MockQueryPort<ListWalletsInputData, ListWalletsOutputData>,
+        MockPort<GetWalletInputData, GetWalletOutputData>,
+        MockPort<DeleteWalletInputData, DeleteWalletOutputData>,
+        MockPort<DepositWalletInputData, DepositWalletOutputData>,
+        MockPort<WithdrawWalletInputData, WithdrawWalletOutputData>,
+        MockPort<TransferInputData, TransferOutputData>,
+        MockPort<UndeleteWalletInputData, UndeleteWalletOutputData>,
+        MockQueryPort<ListTransactionsInputData, ListTransactionsOutputData>,
+        MockQueryPort<GetTrialBalanceInputData, GetTrialBalanceOutputData>,
+    >;
+
+    // a test sets up the ports it calls; calling any other fails it
+    fn mock_ports() -> MockPorts {
+        WalletPorts {
+            create_wallet: MockPort::new(),
+            list_wallets: MockQueryPort::new(),
+            get_wallet: MockPort::new(),
+            delete_wallet: MockPort::new(),
+            deposit_wallet: MockPort::new(),
+            withdraw_wallet: MockPort::new(),
+            transfer: MockPort::new(),
+            undelete_wallet: MockPort::new(),
+            list_transactions: MockQueryPort::new(),
+            get_trial_balance: MockQueryPort::new(),
+        }
+    }
+
     fn new_wallet() -> Wallet {
         WalletBuilder::default()
             .id("01F8MECHZX3TBDSZ7XRADM79XE".parse::<Id<Wallet>>().unwrap())
-            .balance("2000".parse::<Money<JPY>>().unwrap())
+            .owner("kzmake".parse::<Owner>().unwrap())
+            .balance("2000 JPY".parse::<Money>().unwrap())
             .build()
             .unwrap()
     }
 
-    #[test]
-    fn test_create_wallet_handle_ok() {
-        let entity = new_wallet();
-        let view_model = query::view::Wallet {
+    fn new_view_model() -> query::view::Wallet {
+        query::view::Wallet {
             id: new_wallet().id().to_string(),
-            balance: new_wallet().balance().to_string(),
-        };
+            owner: new_wallet().owner().to_string(),
+            balance: new_wallet().balance().amount().to_string(),
+            currency: new_wallet().balance().currency().to_string(),
+            version: new_wallet().version().value() as i64,
+            status: "ACTIVE".to_string(),
+            transaction_count: 3,
+            last_activity_at: SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(30),
+        }
+    }
 
-        let mut create = MockPort::<CreateWalletInputData, CreateWalletOutputData>::new();
-        let mut list = MockQueryPort::<ListWalletsInputData, ListWalletsOutputData>::new();
-        let mut get = MockPort::<GetWalletInputData, GetWalletOutputData>::new();
-        let mut delete = MockPort::<DeleteWalletInputData, DeleteWalletOutputData>::new();
-        create
-            .expect_handle()
-            .returning(|_| Ok(CreateWalletOutputData::new(entity)));
-        list.expect_handle()
-            .returning(|_| Ok(ListWalletsOutputData::new(vec![view_model])));
-        get.expect_handle()
-            .returning(|_| Ok(GetWalletOutputData::new(entity)));
-        delete
+    #[tokio::test]
+    async fn test_create_wallet_handle_ok() {
+        let entity = new_wallet();
+
+        let mut ports = mock_ports();
+        ports
+            .create_wallet
             .expect_handle()
-            .returning(|_| Ok(DeleteWalletOutputData::new()));
-        let sut = WalletController::new(create, list, get, delete);
+            .returning(|_| Ok(CreateWalletOutputData::new(new_wallet())));
+        let sut = WalletController::new(ports);
 
         assert_eq!(
             sut.create(Request::new(CreateRequest {
-                owner: "alice".to_string(),
+                owner: "kzmake".to_string(),
+                currency: "JPY".to_string(),
+                idempotency_key: "".to_string(),
             }))
+            .await
             .unwrap()
             .get_ref(),
             Response::new(CreateResponse {
                 wallet: Some(PBWallet {
                     id: entity.id().to_string(),
-                    owner: "alice".to_string(),
-                    balance: entity.balance().to_string(),
+                    owner: entity.owner().to_string(),
+                    balance: entity.balance().amount().to_string(),
+                    currency: entity.balance().currency().to_string(),
+                    deleted: false,
+                    etag: to_etag(entity.version()),
+                    status: "ACTIVE".to_string(),
+                    transaction_count: 0,
+                    last_activity_time: None,
                 }),
             })
             .get_ref(),
         );
     }
 
-    #[test]
-    fn test_create_wallet_handle_err() {
+    #[tokio::test]
+    async fn test_create_wallet_handle_err() {
         let _entity = new_wallet();
 
-        let mut create = MockPort::<CreateWalletInputData, CreateWalletOutputData>::new();
-        let mut list = MockQueryPort::<ListWalletsInputData, ListWalletsOutputData>::new();
-        let mut get = MockPort::<GetWalletInputData, GetWalletOutputData>::new();
-        let mut delete = MockPort::<DeleteWalletInputData, DeleteWalletOutputData>::new();
-        create.expect_handle().returning(|_| bail!("error"));
-        list.expect_handle().returning(|_| bail!("error"));
-        get.expect_handle().returning(|_| bail!("error"));
-        delete.expect_handle().returning(|_| bail!("error"));
-        let sut = WalletController::new(create, list, get, delete);
+        let mut ports = mock_ports();
+        ports
+            .create_wallet
+            .expect_handle()
+            .returning(|_| Err(Error::internal(anyhow!("error"))));
+        let sut = WalletController::new(ports);
 
         assert!(sut
             .create(Request::new(CreateRequest {
-                owner: "alice".to_string(),
+                owner: "kzmake".to_string(),
+                currency: "JPY".to_string(),
+                idempotency_key: "".to_string(),
             }))
+            .await
             .is_err());
     }
 
-    #[test]
-    fn test_get_wallet_handle_ok() {
+    #[tokio::test]
+    async fn test_list_wallets_handle_ok() {
         let entity = new_wallet();
-        let view_model = query::view::Wallet {
-            id: new_wallet().id().to_string(),
-            balance: new_wallet().balance().to_string(),
-        };
 
-        let mut create = MockPort::<CreateWalletInputData, CreateWalletOutputData>::new();
-        let mut list = MockQueryPort::<ListWalletsInputData, ListWalletsOutputData>::new();
-        let mut get = MockPort::<GetWalletInputData, GetWalletOutputData>::new();
-        let mut delete = MockPort::<DeleteWalletInputData, DeleteWalletOutputData>::new();
-        create
-            .expect_handle()
-            .returning(|_| Ok(CreateWalletOutputData::new(entity)));
-        list.expect_handle()
-            .returning(|_| Ok(ListWalletsOutputData::new(vec![view_model])));
-        get.expect_handle()
-            .returning(|_| Ok(GetWalletOutputData::new(entity)));
-        delete
+        let mut ports = mock_ports();
+        ports.list_wallets.expect_handle().returning(|_| {
+            Ok(ListWalletsOutputData::new(
+                vec![new_view_model()],
+                "".to_string(),
+                1,
+            ))
+        });
+        let sut = WalletController::new(ports);
+
+        assert_eq!(
+            sut.list(Request::new(ListRequest {
+                owner: entity.owner().to_string(),
+                page_size: 10,
+                page_token: "".to_string(),
+                show_deleted: false,
+            }))
+            .await
+            .unwrap()
+            .get_ref(),
+            Response::new(ListResponse {
+                wallets: vec![PBWallet {
+                    id: entity.id().to_string(),
+                    owner: entity.owner().to_string(),
+                    balance: entity.balance().amount().to_string(),
+                    currency: entity.balance().currency().to_string(),
+                    deleted: false,
+                    etag: to_etag(entity.version()),
+                    status: "ACTIVE".to_string(),
+                    transaction_count: 3,
+                    last_activity_time: Some(prost_types::Timestamp {
+                        seconds: 30,
+                        nanos: 0
+                    }),
+                }],
+                next_page_token: "".to_string(),
+                total_size: 1,
+            })
+            .get_ref(),
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_wallet_handle_ok() {
+        let entity = new_wallet();
+
+        let mut ports = mock_ports();
+        ports
+            .get_wallet
             .expect_handle()
-            .returning(|_| Ok(DeleteWalletOutputData::new()));
-        let sut = WalletController::new(create, list, get, delete);
+            .returning(|_| Ok(GetWalletOutputData::new(new_wallet())));
+        let sut = WalletController::new(ports);
 
         assert_eq!(
             sut.get(Request::new(GetRequest {
                 id: entity.id().to_string(),
             }))
+            .await
             .unwrap()
             .get_ref(),
             Response::new(GetResponse {
                 wallet: Some(PBWallet {
                     id: entity.id().to_string(),
-                    owner: "alice".to_string(),
-                    balance: entity.balance().to_string(),
+                    owner: entity.owner().to_string(),
+                    balance: entity.balance().amount().to_string(),
+                    currency: entity.balance().currency().to_string(),
+                    deleted: false,
+                    etag: to_etag(entity.version()),
+                    status: "ACTIVE".to_string(),
+                    transaction_count: 0,
+                    last_activity_time: None,
                 }),
             })
             .get_ref(),
         );
     }
 
-    #[test]
-    fn test_get_wallet_handle_err() {
+    #[tokio::test]
+    async fn test_get_wallet_handle_err() {
         let entity = new_wallet();
 
-        let mut create = MockPort::<CreateWalletInputData, CreateWalletOutputData>::new();
-        let mut list = MockQueryPort::<ListWalletsInputData, ListWalletsOutputData>::new();
-        let mut get = MockPort::<GetWalletInputData, GetWalletOutputData>::new();
-        let mut delete = MockPort::<DeleteWalletInputData, DeleteWalletOutputData>::new();
-        create.expect_handle().returning(|_| bail!("error"));
-        list.expect_handle().returning(|_| bail!("error"));
-        get.expect_handle().returning(|_| bail!("error"));
-        delete.expect_handle().returning(|_| bail!("error"));
-        let sut = WalletController::new(create, list, get, delete);
+        let mut ports = mock_ports();
+        ports
+            .get_wallet
+            .expect_handle()
+            .returning(|_| Err(Error::internal(anyhow!("error"))));
+        let sut = WalletController::new(ports);
 
         assert!(sut
             .get(Request::new(GetRequest {
                 id: entity.id().to_string()
             }))
+            .await
             .is_err());
     }
+
+    #[tokio::test]
+    async fn test_deposit_wallet_handle_ok() {
+        let entity = new_wallet();
+
+        let mut ports = mock_ports();
+        ports
+            .deposit_wallet
+            .expect_handle()
+            .returning(|_| Ok(DepositWalletOutputData::new(new_wallet())));
+        let sut = WalletController::new(ports);
+
+        assert_eq!(
+            sut.deposit(Request::new(DepositRequest {
+                id: entity.id().to_string(),
+                amount: "1000".to_string(),
+                etag: "".to_string(),
+                idempotency_key: "".to_string(),
+                memo: "".to_string(),
+            }))
+            .await
+            .unwrap()
+            .get_ref(),
+            Response::new(DepositResponse {
+                wallet: Some(PBWallet {
+                    id: entity.id().to_string(),
+                    owner: entity.owner().to_string(),
+                    balance: entity.balance().amount().to_string(),
+                    currency: entity.balance().currency().to_string(),
+                    deleted: false,
+                    etag: to_etag(entity.version()),
+                    status: "ACTIVE".to_string(),
+                    transaction_count: 0,
+                    last_activity_time: None,
+                }),
+            })
+            .get_ref(),
+        );
+    }
+
+    #[tokio::test]
+    async fn test_deposit_wallet_handle_err() {
+        let entity = new_wallet();
+
+        let mut ports = mock_ports();
+        ports
+            .deposit_wallet
+            .expect_handle()
+            .returning(|_| Err(Error::internal(anyhow!("error"))));
+        let sut = WalletController::new(ports);
+
+        assert!(sut
+            .deposit(Request::new(DepositRequest {
+                id: entity.id().to_string(),
+                amount: "1000".to_string(),
+                etag: "".to_string(),
+                idempotency_key: "".to_string(),
+                memo: "".to_string(),
+            }))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_withdraw_wallet_handle_ok() {
+        let entity = new_wallet();
+
+        let mut ports = mock_ports();
+        ports
+            .withdraw_wallet
+            .expect_handle()
+            .returning(|_| Ok(WithdrawWalletOutputData::new(new_wallet())));
+        let sut = WalletController::new(ports);
+
+        assert_eq!(
+            sut.withdraw(Request::new(WithdrawRequest {
+                id: entity.id().to_string(),
+                amount: "1000".to_string(),
+                etag: "".to_string(),
+                idempotency_key: "".to_string(),
+                memo: "".to_string(),
+            }))
+            .await
+            .unwrap()
+            .get_ref(),
+            Response::new(WithdrawResponse {
+                wallet: Some(PBWallet {
+                    id: entity.id().to_string(),
+                    owner: entity.owner().to_string(),
+                    balance: entity.balance().amount().to_string(),
+                    currency: entity.balance().currency().to_string(),
+                    deleted: false,
+                    etag: to_etag(entity.version()),
+                    status: "ACTIVE".to_string(),
+                    transaction_count: 0,
+                    last_activity_time: None,
+                }),
+            })
+            .get_ref(),
+        );
+    }
+
+    #[tokio::test]
+    async fn test_withdraw_wallet_handle_err() {
+        let entity = new_wallet();
+
+        let mut ports = mock_ports();
+        ports
+            .withdraw_wallet
+            .expect_handle()
+            .returning(|_| Err(Error::internal(anyhow!("error"))));
+        let sut = WalletController::new(ports);
+
+        assert!(sut
+            .withdraw(Request::new(WithdrawRequest {
+                id: entity.id().to_string(),
+                amount: "1000".to_string(),
+                etag: "".to_string(),
+                idempotency_key: "".to_string(),
+                memo: "".to_string(),
+            }))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_transfer_handle_ok() {
+        let entity = new_wallet();
+
+        let mut ports = mock_ports();
+        ports
+            .transfer
+            .expect_handle()
+            .returning(|_| Ok(TransferOutputData::new(new_wallet(), new_wallet())));
+        let sut = WalletController::new(ports);
+
+        assert_eq!(
+            sut.transfer(Request::new(TransferRequest {
+                from_id: entity.id().to_string(),
+                to_id: entity.id().to_string(),
+                amount: "1000".to_string(),
+                from_etag: "".to_string(),
+                to_etag: "".to_string(),
+                idempotency_key: "".to_string(),
+                memo: "".to_string(),
+            }))
+            .await
+            .unwrap()
+            .get_ref(),
+            Response::new(TransferResponse {
+                from: Some(PBWallet {
+                    id: entity.id().to_string(),
+                    owner: entity.owner().to_string(),
+                    balance: entity.balance().amount().to_string(),
+                    currency: entity.balance().currency().to_string(),
+                    deleted: false,
+                    etag: to_etag(entity.version()),
+                    status: "ACTIVE".to_string(),
+                    transaction_count: 0,
+                    last_activity_time: None,
+                }),
+                to: Some(PBWallet {
+                    id: entity.id().to_string(),
+                    owner: entity.owner().to_string(),
+                    balance: entity.balance().amount().to_string(),
+                    currency: entity.balance().currency().to_string(),
+                    deleted: false,
+                    etag: to_etag(entity.version()),
+                    status: "ACTIVE".to_string(),
+                    transaction_count: 0,
+                    last_activity_time: None,
+                }),
+            })
+            .get_ref(),
+        );
+    }
+
+    #[tokio::test]
+    async fn test_transfer_handle_err() {
+        let entity = new_wallet();
+
+        let mut ports = mock_ports();
+        ports
+            .transfer
+            .expect_handle()
+            .returning(|_| Err(Error::internal(anyhow!("error"))));
+        let sut = WalletController::new(ports);
+
+        assert!(sut
+            .transfer(Request::new(TransferRequest {
+                from_id: entity.id().to_string(),
+                to_id: entity.id().to_string(),
+                amount: "1000".to_string(),
+                from_etag: "".to_string(),
+                to_etag: "".to_string(),
+                idempotency_key: "".to_string(),
+                memo: "".to_string(),
+            }))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_wallet_handle_invalid_id() {
+        let mut ports = mock_ports();
+        ports
+            .get_wallet
+            .expect_handle()
+            .returning(|input| Err(input.id.parse::<Id<Wallet>>().unwrap_err().into()));
+        let sut = WalletController::new(ports);
+
+        assert_eq!(
+            sut.get(Request::new(GetRequest {
+                id: "hogehoge".to_string()
+            }))
+            .await
+            .unwrap_err()
+            .code(),
+            tonic::Code::InvalidArgument
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_wallet_handle_invalid_owner() {
+        let mut ports = mock_ports();
+        ports
+            .create_wallet
+            .expect_handle()
+            .returning(|input| Err(input.owner.parse::<Owner>().unwrap_err().into()));
+        let sut = WalletController::new(ports);
+
+        assert_eq!(
+            sut.create(Request::new(CreateRequest {
+                owner: "".to_string(),
+                currency: "JPY".to_string(),
+                idempotency_key: "".to_string(),
+            }))
+            .await
+            .unwrap_err()
+            .code(),
+            tonic::Code::InvalidArgument
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_wallet_handle_not_found() {
+        let mut ports = mock_ports();
+        ports
+            .get_wallet
+            .expect_handle()
+            .returning(|input| Err(Error::not_found("wallet", input.id)));
+        ports
+            .deposit_wallet
+            .expect_handle()
+            .returning(|_| Err(Error::unavailable(anyhow!("connection refused"))));
+        let sut = WalletController::new(ports);
+
+        assert_eq!(
+            sut.get(Request::new(GetRequest {
+                id: new_wallet().id().to_string()
+            }))
+            .await
+            .unwrap_err()
+            .code(),
+            tonic::Code::NotFound
+        );
+        assert_eq!(
+            sut.deposit(Request::new(DepositRequest {
+                id: new_wallet().id().to_string(),
+                amount: "1000".to_string(),
+                etag: "".to_string(),
+                idempotency_key: "".to_string(),
+                memo: "".to_string(),
+            }))
+            .await
+            .unwrap_err()
+            .code(),
+            tonic::Code::Unavailable
+        );
+    }
+
+    #[tokio::test]
+    async fn test_undelete_wallet_handle_ok() {
+        let entity = new_wallet();
+
+        let mut ports = mock_ports();
+        ports
+            .undelete_wallet
+            .expect_handle()
+            .returning(|_| Ok(UndeleteWalletOutputData::new(new_wallet())));
+        let sut = WalletController::new(ports);
+
+        assert_eq!(
+            sut.undelete(Request::new(UndeleteRequest {
+                id: entity.id().to_string(),
+                idempotency_key: "".to_string(),
+            }))
+            .await
+            .unwrap()
+            .get_ref(),
+            Response::new(UndeleteResponse {
+                wallet: Some(PBWallet {
+                    id: entity.id().to_string(),
+                    owner: entity.owner().to_string(),
+                    balance: entity.balance().amount().to_string(),
+                    currency: entity.balance().currency().to_string(),
+                    deleted: false,
+                    etag: to_etag(entity.version()),
+                    status: "ACTIVE".to_string(),
+                    transaction_count: 0,
+                    last_activity_time: None,
+                }),
+            })
+            .get_ref(),
+        );
+    }
+
+    #[tokio::test]
+    async fn test_undelete_wallet_handle_retention_expired() {
+        let mut ports = mock_ports();
+        ports
+            .undelete_wallet
+            .expect_handle()
+            .returning(|input| Err(UndeleteWalletError::RetentionExpired(input.id).into()));
+        let sut = WalletController::new(ports);
+
+        assert_eq!(
+            sut.undelete(Request::new(UndeleteRequest {
+                id: new_wallet().id().to_string(),
+                idempotency_key: "".to_string(),
+            }))
+            .await
+            .unwrap_err()
+            .code(),
+            tonic::Code::FailedPrecondition
+        );
+    }
+
+    #[test]
+    fn test_etag() {
+        assert_eq!(to_etag(Version::new(3)), "\"3\"".to_string());
+        assert_eq!(from_etag("\"3\""), "3".to_string());
+        assert_eq!(from_etag("W/\"3\""), "3".to_string());
+        assert_eq!(from_etag(""), "".to_string());
+    }
+
+    #[tokio::test]
+    async fn test_deposit_wallet_handle_stale_etag() {
+        let mut ports = mock_ports();
+        ports.deposit_wallet.expect_handle().returning(|input| {
+            assert_eq!(input.version, "1".to_string());
+            Err(PreconditionError::VersionMismatch {
+                id: input.id,
+                expected: Version::new(1),
+                actual: Version::new(2),
+            }
+            .into())
+        });
+        ports.withdraw_wallet.expect_handle().returning(|input| {
+            Err(ConcurrencyError::VersionConflict {
+                id: input.id,
+                expected: Version::new(1),
+            }
+            .into())
+        });
+        let sut = WalletController::new(ports);
+
+        assert_eq!(
+            sut.deposit(Request::new(DepositRequest {
+                id: new_wallet().id().to_string(),
+                amount: "1000".to_string(),
+                etag: "\"1\"".to_string(),
+                idempotency_key: "".to_string(),
+                memo: "".to_string(),
+            }))
+            .await
+            .unwrap_err()
+            .code(),
+            tonic::Code::FailedPrecondition
+        );
+        assert_eq!(
+            sut.withdraw(Request::new(WithdrawRequest {
+                id: new_wallet().id().to_string(),
+                amount: "1000".to_string(),
+                etag: "".to_string(),
+                idempotency_key: "".to_string(),
+                memo: "".to_string(),
+            }))
+            .await
+            .unwrap_err()
+            .code(),
+            tonic::Code::Aborted
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_transactions_handle_ok() {
+        let mut ports = mock_ports();
+        ports.list_transactions.expect_handle().returning(|input| {
+            assert_eq!(
+                input.start_time,
+                Some(SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(10))
+            );
+            assert_eq!(input.end_time, None);
+            Ok(ListTransactionsOutputData::new(
+                vec![query::view::Transaction {
+                    id: "01F8MECHZX3TBDSZ7XRADM79XA".to_string(),
+                    wallet_id: input.wallet_id,
+                    kind: "TRANSFER_IN".to_string(),
+                    amount: "100".to_string(),
+                    balance: "2100".to_string(),
+                    currency: "JPY".to_string(),
+                    counterparty: Some("01F8MECHZX3TBDSZ7XRADM79XF".to_string()),
+                    memo: "lunch".to_string(),
+                    create_at: SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(20),
+                }],
+                "".to_string(),
+                1,
+            ))
+        });
+        let sut = WalletController::new(ports);
+
+        assert_eq!(
+            sut.list_transactions(Request::new(ListTransactionsRequest {
+                wallet_id: new_wallet().id().to_string(),
+                page_size: 0,
+                page_token: "".to_string(),
+                start_time: Some(prost_types::Timestamp {
+                    seconds: 10,
+                    nanos: 0
+                }),
+                end_time: None,
+            }))
+            .await
+            .unwrap()
+            .get_ref(),
+            Response::new(ListTransactionsResponse {
+                transactions: vec![PBTransaction {
+                    id: "01F8MECHZX3TBDSZ7XRADM79XA".to_string(),
+                    wallet_id: new_wallet().id().to_string(),
+                    kind: "TRANSFER_IN".to_string(),
+                    amount: "100".to_string(),
+                    balance: "2100".to_string(),
+                    currency: "JPY".to_string(),
+                    counterparty: "01F8MECHZX3TBDSZ7XRADM79XF".to_string(),
+                    memo: "lunch".to_string(),
+                    create_time: Some(prost_types::Timestamp {
+                        seconds: 20,
+                        nanos: 0
+                    }),
+                }],
+                next_page_token: "".to_string(),
+                total_size: 1,
+            })
+            .get_ref(),
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_trial_balance_handle_ok() {
+        let mut ports = mock_ports();
+        ports.get_trial_balance.expect_handle().returning(|input| {
+            assert_eq!(input.currency, "JPY".to_string());
+            Ok(GetTrialBalanceOutputData::new(
+                vec![query::view::LedgerAccount {
+                    account: "funding:JPY".to_string(),
+                    kind: "ASSET".to_string(),
+                    currency: "JPY".to_string(),
+                    debit: "2000".to_string(),
+                    credit: "0".to_string(),
+                    balance: "2000".to_string(),
+                }],
+                vec![query::view::TrialBalanceTotal {
+                    currency: "JPY".to_string(),
+                    debit: "2000".to_string(),
+                    credit: "2000".to_string(),
+                    balanced: true,
+                }],
+                true,
+            ))
+        });
+        let sut = WalletController::new(ports);
+
+        assert_eq!(
+            sut.get_trial_balance(Request::new(GetTrialBalanceRequest {
+                currency: "JPY".to_string(),
+            }))
+            .await
+            .unwrap()
+            .get_ref(),
+            Response::new(GetTrialBalanceResponse {
+                accounts: vec![PBLedgerAccount {
+                    account: "funding:JPY".to_string(),
+                    kind: "ASSET".to_string(),
+                    currency: "JPY".to_string(),
+                    debit: "2000".to_string(),
+                    credit: "0".to_string(),
+                    balance: "2000".to_string(),
+                }],
+                totals: vec![PBTrialBalanceTotal {
+                    currency: "JPY".to_string(),
+                    debit: "2000".to_string(),
+                    credit: "2000".to_string(),
+                    balanced: true,
+                }],
+                balanced: true,
+            })
+            .get_ref(),
+        );
+    }
 }
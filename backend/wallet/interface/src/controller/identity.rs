@@ -0,0 +1,73 @@
+use tonic::Request;
+
+/// The caller as proven by the client certificate it presented over mutual TLS.
+///
+/// The server adds it to the extensions of every request received on such a connection, and
+/// idempotency keys are scoped to it so that one client is never replayed another's response.
+/// Requests over plaintext or without a client certificate carry none.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ClientIdentity {
+    /// The common name of the certificate's subject.
+    pub common_name: Option<String>,
+    /// The DNS names and URIs, e.g. SPIFFE ids, among the subject alternative names.
+    pub alt_names: Vec<String>,
+    /// The hex-encoded SHA-256 digest of the certificate.
+    pub fingerprint: String,
+}
+
+impl ClientIdentity {
+    pub fn of<T>(request: &Request<T>) -> Option<&ClientIdentity> {
+        request.extensions().get::<ClientIdentity>()
+    }
+
+    /// The most specific name the certificate gives: a URI such as a SPIFFE id, then the common
+    /// name, then a DNS name, and the fingerprint when it has none.
+    pub fn name(&self) -> &str {
+        let uri = self.alt_names.iter().find(|name| name.contains("://"));
+        uri.or(self.common_name.as_ref())
+            .or_else(|| self.alt_names.first())
+            .unwrap_or(&self.fingerprint)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_identity_of() {
+        let identity = ClientIdentity {
+            common_name: Some("gateway".to_string()),
+            alt_names: vec!["spiffe://osaifu/gateway".to_string()],
+            fingerprint: "ab".repeat(32),
+        };
+
+        let mut request = Request::new(());
+        assert_eq!(ClientIdentity::of(&request), None);
+
+        request.extensions_mut().insert(identity.clone());
+        assert_eq!(ClientIdentity::of(&request), Some(&identity));
+    }
+
+    #[test]
+    fn test_client_identity_name() {
+        let mut identity = ClientIdentity {
+            common_name: Some("gateway".to_string()),
+            alt_names: vec![
+                "gateway.osaifu.svc".to_string(),
+                "spiffe://osaifu/gateway".to_string(),
+            ],
+            fingerprint: "ab".repeat(32),
+        };
+        assert_eq!(identity.name(), "spiffe://osaifu/gateway");
+
+        identity.alt_names.pop();
+        assert_eq!(identity.name(), "gateway");
+
+        identity.common_name = None;
+        assert_eq!(identity.name(), "gateway.osaifu.svc");
+
+        identity.alt_names.clear();
+        assert_eq!(identity.name(), "ab".repeat(32));
+    }
+}
@@ -1,5 +1,10 @@
 #[allow(clippy::module_inception)]
 mod controller;
+mod identity;
+mod status;
 
 pub use self::controller::Controller;
 pub use self::controller::WalletController;
+pub use self::controller::WalletPorts;
+pub use self::identity::ClientIdentity;
+pub(crate) use self::status::to_status;
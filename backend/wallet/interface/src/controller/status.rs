@@ -0,0 +1,163 @@
+use domain::error::Error;
+use prost::Message;
+use std::collections::HashMap;
+use tonic::{Code, Status};
+use tracing::error;
+
+const ERROR_DOMAIN: &str = "osaifu.wallet.v1";
+
+// google.rpc.Status and the error details carried in it. Only their wire format matters, so
+// they are declared here instead of compiling googleapis' rpc protos.
+#[derive(Clone, PartialEq, Message)]
+struct RpcStatus {
+    #[prost(int32, tag = "1")]
+    code: i32,
+    #[prost(string, tag = "2")]
+    message: String,
+    #[prost(message, repeated, tag = "3")]
+    details: Vec<prost_types::Any>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct ErrorInfo {
+    #[prost(string, tag = "1")]
+    reason: String,
+    #[prost(string, tag = "2")]
+    domain: String,
+    #[prost(map = "string, string", tag = "3")]
+    metadata: HashMap<String, String>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct BadRequest {
+    #[prost(message, repeated, tag = "1")]
+    field_violations: Vec<FieldViolation>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct FieldViolation {
+    #[prost(string, tag = "1")]
+    field: String,
+    #[prost(string, tag = "2")]
+    description: String,
+}
+
+fn to_any(type_name: &str, message: impl Message) -> prost_types::Any {
+    prost_types::Any {
+        type_url: format!("type.googleapis.com/{}", type_name),
+        value: message.encode_to_vec(),
+    }
+}
+
+/// Maps `e` to the status code of its class, with an ErrorInfo detail naming the reason and a
+/// BadRequest detail for invalid arguments. Unavailable and internal errors keep their cause
+/// out of the message, so it is logged here, in the span of the request, before it is dropped.
+pub(crate) fn to_status(e: Error) -> Status {
+    let mut metadata = HashMap::new();
+    let mut details = vec![];
+    let (code, message) = match &e {
+        Error::NotFound { resource, id } => {
+            metadata.insert("resource".to_string(), resource.to_string());
+            metadata.insert("id".to_string(), id.clone());
+            (Code::NotFound, e.to_string())
+        }
+        Error::InvalidArgument { field, description } => {
+            details.push(to_any(
+                "google.rpc.BadRequest",
+                BadRequest {
+                    field_violations: vec![FieldViolation {
+                        field: field.to_string(),
+                        description: description.clone(),
+                    }],
+                },
+            ));
+            (Code::InvalidArgument, e.to_string())
+        }
+        Error::Conflict { .. } => (Code::Aborted, e.to_string()),
+        Error::FailedPrecondition { .. } => (Code::FailedPrecondition, e.to_string()),
+        Error::Unavailable(cause) => {
+            error!(cause = %format_args!("{:#}", cause), "service is unavailable");
+            (Code::Unavailable, "service is unavailable".to_string())
+        }
+        Error::Internal(cause) => {
+            error!(cause = %format_args!("{:#}", cause), "internal error");
+            (Code::Internal, "internal error".to_string())
+        }
+    };
+    details.insert(
+        0,
+        to_any(
+            "google.rpc.ErrorInfo",
+            ErrorInfo {
+                reason: e.reason().to_string(),
+                domain: ERROR_DOMAIN.to_string(),
+                metadata,
+            },
+        ),
+    );
+
+    let status = RpcStatus {
+        code: code as i32,
+        message: message.clone(),
+        details,
+    };
+
+    Status::with_details(code, message, status.encode_to_vec().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    fn decode(status: &Status) -> (ErrorInfo, Option<BadRequest>) {
+        let status = RpcStatus::decode(status.details()).unwrap();
+        let info = ErrorInfo::decode(status.details[0].value.as_slice()).unwrap();
+        let bad_request = status
+            .details
+            .iter()
+            .find(|d| d.type_url == "type.googleapis.com/google.rpc.BadRequest")
+            .map(|d| BadRequest::decode(d.value.as_slice()).unwrap());
+        (info, bad_request)
+    }
+
+    #[test]
+    fn test_to_status() {
+        let status = to_status(Error::not_found("wallet", "01F8MECHZX3TBDSZ7XRADM79XE"));
+        assert_eq!(status.code(), Code::NotFound);
+        let (info, bad_request) = decode(&status);
+        assert_eq!(info.reason, "NOT_FOUND");
+        assert_eq!(info.domain, ERROR_DOMAIN);
+        assert_eq!(info.metadata["id"], "01F8MECHZX3TBDSZ7XRADM79XE");
+        assert!(bad_request.is_none());
+
+        let status = to_status(Error::invalid_argument("amount", "must be positive"));
+        assert_eq!(status.code(), Code::InvalidArgument);
+        let (info, bad_request) = decode(&status);
+        assert_eq!(info.reason, "INVALID_ARGUMENT");
+        assert_eq!(
+            bad_request.unwrap().field_violations,
+            vec![FieldViolation {
+                field: "amount".to_string(),
+                description: "must be positive".to_string(),
+            }]
+        );
+
+        let status = to_status(Error::conflict("VERSION_CONFLICT", "conflict"));
+        assert_eq!(status.code(), Code::Aborted);
+        assert_eq!(decode(&status).0.reason, "VERSION_CONFLICT");
+
+        let status = to_status(Error::failed_precondition("INSUFFICIENT_BALANCE", "short"));
+        assert_eq!(status.code(), Code::FailedPrecondition);
+        assert_eq!(decode(&status).0.reason, "INSUFFICIENT_BALANCE");
+
+        let status = to_status(Error::unavailable(anyhow!("connection refused")));
+        assert_eq!(status.code(), Code::Unavailable);
+        assert!(!status.message().contains("connection refused"));
+
+        let status = to_status(Error::internal(anyhow!("secret")));
+        assert_eq!(status.code(), Code::Internal);
+        assert!(!status.message().contains("secret"));
+        assert_eq!(decode(&status).0.reason, "INTERNAL");
+    }
+}
@@ -0,0 +1,597 @@
+use crate::controller::to_status;
+use crate::controller::ClientIdentity;
+use async_trait::async_trait;
+use derive_new::new;
+use domain::error::{Error, Result};
+use prost::Message;
+use sha2::{Digest, Sha256};
+use std::future::Future;
+use std::time::{Duration, SystemTime};
+use tonic::{Request, Response, Status};
+
+pub const IDEMPOTENCY_KEY_METADATA: &str = "idempotency-key";
+const IDEMPOTENCY_KEY_MAX_LENGTH: usize = 255;
+
+/// State of an idempotency key after trying to claim it for a request.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Claim {
+    /// The key was free and is now held by the caller's transaction, which must `complete` it.
+    Acquired,
+    /// A request with the key already succeeded with `response`.
+    Completed {
+        request_hash: String,
+        response: Vec<u8>,
+    },
+}
+
+/// Keeps the responses of requests by their idempotency key.
+///
+/// A key is claimed and completed in the transaction `begin` opens, and `within` runs the
+/// request so that its changes are written in that transaction too. The key and its response
+/// are thus saved along with the changes or not at all. A request claiming a key that another
+/// one holds waits for that transaction to end.
+#[async_trait]
+pub trait IdempotencyRepository: Send + Sync {
+    type Tx: Send + Sync + 'static;
+
+    async fn begin(&self) -> Result<Self::Tx, Error>;
+    async fn within<F>(&self, tx: &Self::Tx, f: F) -> F::Output
+    where
+        F: Future + Send,
+        F::Output: Send;
+    async fn claim(
+        &self,
+        tx: &Self::Tx,
+        key: &str,
+        request_hash: &str,
+        expires_at: SystemTime,
+    ) -> Result<Claim, Error>;
+    async fn complete(
+        &self,
+        tx: &Self::Tx,
+        key: &str,
+        response: Vec<u8>,
+        expires_at: SystemTime,
+    ) -> Result<(), Error>;
+    async fn commit(&self, tx: Self::Tx) -> Result<(), Error>;
+    async fn rollback(&self, tx: Self::Tx) -> Result<(), Error>;
+    /// Removes keys that expired before `now` and returns how many were removed.
+    async fn purge(&self, now: SystemTime) -> Result<usize, Error>;
+}
+
+/// Replays the stored response when a mutating request is retried with the same idempotency
+/// key, so that client retries after a timeout do not apply the mutation twice.
+///
+/// A key is only ever replayed to the client that used it: a client presenting a certificate
+/// gets the key refused as used for a different request when another client used it first.
+#[derive(new)]
+pub struct Idempotency<R>
+where
+    R: IdempotencyRepository,
+{
+    repository: R,
+    ttl: Duration,
+}
+
+impl<R> Idempotency<R>
+where
+    R: IdempotencyRepository,
+{
+    /// Runs `f` at most once per idempotency key. The key is taken from `key`, usually the
+    /// request's `idempotency_key` field, or else from the `idempotency-key` metadata; requests
+    /// without either are passed through.
+    pub async fn handle<Req, Res, F, Fut>(
+        &self,
+        method: &str,
+        key: &str,
+        request: Request<Req>,
+        f: F,
+    ) -> Result<Response<Res>, Status>
+    where
+        Req: Message,
+        Res: Message + Default,
+        F: FnOnce(Request<Req>) -> Fut,
+        Fut: Future<Output = Result<Response<Res>, Status>> + Send,
+    {
+        let key = match key {
+            "" => match request.metadata().get(IDEMPOTENCY_KEY_METADATA) {
+                Some(value) => value
+                    .to_str()
+                    .map_err(|_| to_status(invalid_key("must be ASCII")))?
+                    .to_string(),
+                None => return f(request).await,
+            },
+            key => key.to_string(),
+        };
+        if key.is_empty() || key.len() > IDEMPOTENCY_KEY_MAX_LENGTH {
+            return Err(to_status(invalid_key(format!(
+                "must be 1 to {} characters",
+                IDEMPOTENCY_KEY_MAX_LENGTH
+            ))));
+        }
+
+        let tx = self.repository.begin().await.map_err(to_status)?;
+        match self.run(&tx, method, &key, request, f).await {
+            Ok(Run::Done(response)) => {
+                self.repository.commit(tx).await.map_err(to_status)?;
+                Ok(response)
+            }
+            Ok(Run::Replayed(response)) => {
+                self.rollback(tx).await;
+                Ok(response)
+            }
+            // failed requests leave no key behind so that the client can retry them
+            Err(status) => {
+                self.rollback(tx).await;
+                Err(status)
+            }
+        }
+    }
+
+    async fn run<Req, Res, F, Fut>(
+        &self,
+        tx: &R::Tx,
+        method: &str,
+        key: &str,
+        request: Request<Req>,
+        f: F,
+    ) -> Result<Run<Res>, Status>
+    where
+        Req: Message,
+        Res: Message + Default,
+        F: FnOnce(Request<Req>) -> Fut,
+        Fut: Future<Output = Result<Response<Res>, Status>> + Send,
+    {
+        let request_hash = hash(method, ClientIdentity::of(&request), request.get_ref());
+        match self
+            .repository
+            .claim(tx, key, &request_hash, SystemTime::now() + self.ttl)
+            .await
+            .map_err(to_status)?
+        {
+            Claim::Acquired => {}
+            Claim::Completed {
+                request_hash: stored,
+                response,
+            } if stored == request_hash => {
+                return Res::decode(response.as_slice())
+                    .map(|response| Run::Replayed(Response::new(response)))
+                    .map_err(|e| to_status(Error::internal(e)));
+            }
+            _ => {
+                return Err(to_status(invalid_key(
+                    "was already used for a different request",
+                )));
+            }
+        }
+
+        let response = self.repository.within(tx, f(request)).await?;
+        self.repository
+            .complete(
+                tx,
+                key,
+                response.get_ref().encode_to_vec(),
+                SystemTime::now() + self.ttl,
+            )
+            .await
+            .map_err(to_status)?;
+
+        Ok(Run::Done(response))
+    }
+
+    // the error the request ended with is what the client needs to see, not a failed rollback
+    async fn rollback(&self, tx: R::Tx) {
+        let _ = self.repository.rollback(tx).await;
+    }
+}
+
+enum Run<Res> {
+    /// The request ran and its response is to be saved.
+    Done(Response<Res>),
+    /// The request ran before, and this is the response it had.
+    Replayed(Response<Res>),
+}
+
+// callers without a certificate hash as they did before clients could present one
+fn hash<M: Message>(method: &str, client: Option<&ClientIdentity>, message: &M) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(method.as_bytes());
+    hasher.update([0]);
+    if let Some(client) = client {
+        hasher.update(client.name().as_bytes());
+        hasher.update([0]);
+    }
+    hasher.update(message.encode_to_vec());
+
+    format!("{:x}", hasher.finalize())
+}
+
+fn invalid_key(description: impl std::fmt::Display) -> Error {
+    Error::invalid_argument("idempotency_key", description)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::osaifu_wallet_v1::{CreateRequest, CreateResponse, Wallet as PBWallet};
+    use std::cell::Cell;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    // keys and the count of changes the requests made, as far as their transactions committed
+    struct MockIdempotencyRepository {
+        store: Arc<Mutex<HashMap<String, Claim>>>,
+        changes: Arc<Mutex<usize>>,
+        // how many of the next calls to `complete` fail
+        failing_completes: Mutex<usize>,
+    }
+    impl MockIdempotencyRepository {
+        fn new() -> Self {
+            let m = HashMap::new();
+            Self {
+                store: Arc::new(Mutex::new(m)),
+                changes: Arc::new(Mutex::new(0)),
+                failing_completes: Mutex::new(0),
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct MockTx {
+        // the request hashes of the keys claimed
+        keys: Mutex<HashMap<String, String>>,
+        completed: Mutex<HashMap<String, Claim>>,
+        changes: Mutex<usize>,
+    }
+
+    #[async_trait]
+    impl IdempotencyRepository for MockIdempotencyRepository {
+        type Tx = MockTx;
+
+        async fn begin(&self) -> Result<MockTx, Error> {
+            Ok(MockTx::default())
+        }
+
+        async fn within<F>(&self, tx: &MockTx, f: F) -> F::Output
+        where
+            F: Future + Send,
+            F::Output: Send,
+        {
+            *tx.changes.lock().unwrap() += 1;
+            f.await
+        }
+
+        async fn claim(
+            &self,
+            tx: &MockTx,
+            key: &str,
+            request_hash: &str,
+            _: SystemTime,
+        ) -> Result<Claim, Error> {
+            let m = self.store.lock().unwrap();
+            match m.get(key) {
+                Some(claim) => Ok(claim.clone()),
+                None => {
+                    tx.keys
+                        .lock()
+                        .unwrap()
+                        .insert(key.to_string(), request_hash.to_string());
+                    Ok(Claim::Acquired)
+                }
+            }
+        }
+
+        async fn complete(
+            &self,
+            tx: &MockTx,
+            key: &str,
+            response: Vec<u8>,
+            _: SystemTime,
+        ) -> Result<(), Error> {
+            let mut failing = self.failing_completes.lock().unwrap();
+            if *failing > 0 {
+                *failing -= 1;
+                return Err(Error::unavailable(anyhow::anyhow!("connection reset")));
+            }
+
+            let request_hash = tx.keys.lock().unwrap()[key].clone();
+            tx.completed.lock().unwrap().insert(
+                key.to_string(),
+                Claim::Completed {
+                    request_hash,
+                    response,
+                },
+            );
+            Ok(())
+        }
+
+        async fn commit(&self, tx: MockTx) -> Result<(), Error> {
+            self.store
+                .lock()
+                .unwrap()
+                .extend(tx.completed.into_inner().unwrap());
+            *self.changes.lock().unwrap() += tx.changes.into_inner().unwrap();
+            Ok(())
+        }
+
+        async fn rollback(&self, _: MockTx) -> Result<(), Error> {
+            Ok(())
+        }
+
+        async fn purge(&self, _: SystemTime) -> Result<usize, Error> {
+            Ok(0)
+        }
+    }
+
+    // stands for a database that cannot be reached
+    struct UnavailableIdempotencyRepository;
+
+    #[async_trait]
+    impl IdempotencyRepository for UnavailableIdempotencyRepository {
+        type Tx = ();
+
+        async fn begin(&self) -> Result<(), Error> {
+            Err(Error::unavailable(anyhow::anyhow!("connection refused")))
+        }
+
+        async fn within<F>(&self, _: &(), f: F) -> F::Output
+        where
+            F: Future + Send,
+            F::Output: Send,
+        {
+            f.await
+        }
+
+        async fn claim(&self, _: &(), _: &str, _: &str, _: SystemTime) -> Result<Claim, Error> {
+            Err(Error::unavailable(anyhow::anyhow!("connection refused")))
+        }
+
+        async fn complete(&self, _: &(), _: &str, _: Vec<u8>, _: SystemTime) -> Result<(), Error> {
+            Err(Error::unavailable(anyhow::anyhow!("connection refused")))
+        }
+
+        async fn commit(&self, _: ()) -> Result<(), Error> {
+            Err(Error::unavailable(anyhow::anyhow!("connection refused")))
+        }
+
+        async fn rollback(&self, _: ()) -> Result<(), Error> {
+            Err(Error::unavailable(anyhow::anyhow!("connection refused")))
+        }
+
+        async fn purge(&self, _: SystemTime) -> Result<usize, Error> {
+            Err(Error::unavailable(anyhow::anyhow!("connection refused")))
+        }
+    }
+
+    const METHOD: &str = "/osaifu.wallet.v1.WalletService/Create";
+
+    fn new_request(owner: &str) -> Request<CreateRequest> {
+        Request::new(CreateRequest {
+            owner: owner.to_string(),
+            currency: "JPY".to_string(),
+            idempotency_key: "key".to_string(),
+        })
+    }
+
+    fn new_response(id: &str) -> Response<CreateResponse> {
+        Response::new(CreateResponse {
+            wallet: Some(PBWallet {
+                id: id.to_string(),
+                owner: "kzmake".to_string(),
+                balance: "0".to_string(),
+                currency: "JPY".to_string(),
+                deleted: false,
+                etag: "\"1\"".to_string(),
+                status: "".to_string(),
+                transaction_count: 0,
+                last_activity_time: None,
+            }),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_replay() {
+        let sut = Idempotency::new(MockIdempotencyRepository::new(), Duration::from_secs(60));
+        let calls = Cell::new(0);
+
+        let first = sut
+            .handle(METHOD, "key", new_request("kzmake"), |_| {
+                calls.set(calls.get() + 1);
+                async { Ok(new_response("01F8MECHZX3TBDSZ7XRADM79XE")) }
+            })
+            .await
+            .unwrap();
+        let second = sut
+            .handle(METHOD, "key", new_request("kzmake"), |_| {
+                calls.set(calls.get() + 1);
+                async { Ok(new_response("01F8MECHZX3TBDSZ7XRADM79XF")) }
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(calls.get(), 1);
+        assert_eq!(first.get_ref(), second.get_ref());
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_key_reused() {
+        let sut = Idempotency::new(MockIdempotencyRepository::new(), Duration::from_secs(60));
+
+        sut.handle(METHOD, "key", new_request("kzmake"), |_| async {
+            Ok(new_response("01F8MECHZX3TBDSZ7XRADM79XE"))
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(
+            sut.handle(METHOD, "key", new_request("alice"), |_| async {
+                Ok(new_response("01F8MECHZX3TBDSZ7XRADM79XF"))
+            })
+            .await
+            .unwrap_err()
+            .code(),
+            tonic::Code::InvalidArgument
+        );
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_key_reused_by_other_client() {
+        let sut = Idempotency::new(MockIdempotencyRepository::new(), Duration::from_secs(60));
+        let from = |name: &str| {
+            let mut request = new_request("kzmake");
+            request.extensions_mut().insert(ClientIdentity {
+                common_name: Some(name.to_string()),
+                alt_names: vec![],
+                fingerprint: "ab".repeat(32),
+            });
+            request
+        };
+
+        sut.handle(METHOD, "key", from("gateway"), |_| async {
+            Ok(new_response("01F8MECHZX3TBDSZ7XRADM79XE"))
+        })
+        .await
+        .unwrap();
+
+        // neither another client nor one without a certificate gets the response
+        for request in [from("batch"), new_request("kzmake")] {
+            assert_eq!(
+                sut.handle(METHOD, "key", request, |_| async {
+                    Ok(new_response("01F8MECHZX3TBDSZ7XRADM79XF"))
+                })
+                .await
+                .unwrap_err()
+                .code(),
+                tonic::Code::InvalidArgument
+            );
+        }
+        assert_eq!(
+            sut.handle(METHOD, "key", from("gateway"), |_| async {
+                Ok(new_response("01F8MECHZX3TBDSZ7XRADM79XF"))
+            })
+            .await
+            .unwrap()
+            .get_ref(),
+            new_response("01F8MECHZX3TBDSZ7XRADM79XE").get_ref()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_complete_failed() {
+        let repository = MockIdempotencyRepository::new();
+        *repository.failing_completes.lock().unwrap() = 1;
+        let changes = repository.changes.clone();
+        let sut = Idempotency::new(repository, Duration::from_secs(60));
+        let calls = Cell::new(0);
+
+        // the response could not be saved, so neither are the changes of the request
+        assert_eq!(
+            sut.handle(METHOD, "key", new_request("kzmake"), |_| {
+                calls.set(calls.get() + 1);
+                async { Ok(new_response("01F8MECHZX3TBDSZ7XRADM79XE")) }
+            })
+            .await
+            .unwrap_err()
+            .code(),
+            tonic::Code::Unavailable
+        );
+        assert_eq!(*changes.lock().unwrap(), 0);
+
+        // a retry runs it again, and the retries after that replay it
+        for _ in 0..2 {
+            sut.handle(METHOD, "key", new_request("kzmake"), |_| {
+                calls.set(calls.get() + 1);
+                async { Ok(new_response("01F8MECHZX3TBDSZ7XRADM79XF")) }
+            })
+            .await
+            .unwrap();
+        }
+        assert_eq!(calls.get(), 2);
+        assert_eq!(*changes.lock().unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_error_is_not_recorded() {
+        let sut = Idempotency::new(MockIdempotencyRepository::new(), Duration::from_secs(60));
+
+        assert!(sut
+            .handle::<_, CreateResponse, _, _>(METHOD, "key", new_request("kzmake"), |_| async {
+                Err(Status::internal("error"))
+            })
+            .await
+            .is_err());
+        assert!(sut
+            .handle(METHOD, "key", new_request("kzmake"), |_| async {
+                Ok(new_response("01F8MECHZX3TBDSZ7XRADM79XE"))
+            })
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_key_from_metadata() {
+        let sut = Idempotency::new(MockIdempotencyRepository::new(), Duration::from_secs(60));
+        let calls = Cell::new(0);
+
+        for _ in 0..2 {
+            let mut request = new_request("kzmake");
+            request.get_mut().idempotency_key = "".to_string();
+            request
+                .metadata_mut()
+                .insert(IDEMPOTENCY_KEY_METADATA, "key".parse().unwrap());
+
+            sut.handle(METHOD, "", request, |_| {
+                calls.set(calls.get() + 1);
+                async { Ok(new_response("01F8MECHZX3TBDSZ7XRADM79XE")) }
+            })
+            .await
+            .unwrap();
+        }
+        assert_eq!(calls.get(), 1);
+
+        // without a key every request runs
+        for _ in 0..2 {
+            let mut request = new_request("kzmake");
+            request.get_mut().idempotency_key = "".to_string();
+
+            sut.handle(METHOD, "", request, |_| {
+                calls.set(calls.get() + 1);
+                async { Ok(new_response("01F8MECHZX3TBDSZ7XRADM79XE")) }
+            })
+            .await
+            .unwrap();
+        }
+        assert_eq!(calls.get(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_invalid_key() {
+        let sut = Idempotency::new(MockIdempotencyRepository::new(), Duration::from_secs(60));
+
+        assert_eq!(
+            sut.handle(METHOD, &"k".repeat(256), new_request("kzmake"), |_| async {
+                Ok(new_response("01F8MECHZX3TBDSZ7XRADM79XE"))
+            })
+            .await
+            .unwrap_err()
+            .code(),
+            tonic::Code::InvalidArgument
+        );
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_repository_unavailable() {
+        let sut = Idempotency::new(UnavailableIdempotencyRepository, Duration::from_secs(60));
+
+        assert_eq!(
+            sut.handle(METHOD, "key", new_request("kzmake"), |_| async {
+                Ok(new_response("01F8MECHZX3TBDSZ7XRADM79XE"))
+            })
+            .await
+            .unwrap_err()
+            .code(),
+            tonic::Code::Unavailable
+        );
+    }
+}
@@ -0,0 +1,4 @@
+#[allow(clippy::module_inception)]
+mod idempotency;
+
+pub use self::idempotency::*;
@@ -3,4 +3,9 @@ pub mod osaifu_wallet_v1 {
     pub const FILE_DESCRIPTOR_SET: &[u8] = tonic::include_file_descriptor_set!("osaifu.wallet.v1");
 }
 
+pub mod grpc_health_v1 {
+    tonic::include_proto!("grpc.health.v1");
+}
+
 pub mod controller;
+pub mod idempotency;
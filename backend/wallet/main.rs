@@ -1,38 +1,416 @@
 use anyhow::Result;
-use infrastructure::grpc::Service;
+use clap::Parser;
+use domain::entity::Wallet;
+use domain::event::{EventDispatcher, WalletEvent};
+use domain::repository::*;
+use domain::service::TransferService;
+use infrastructure::broker::{Broker, NatsBroker};
+use infrastructure::config::{Args, Command, Config, Store};
+use infrastructure::grpc::{Health, Service, Tls};
+use infrastructure::metrics::Metrics;
+use infrastructure::postgres::AfterCommitPublisher;
 use infrastructure::postgres::DbPool;
+use infrastructure::postgres::DbUnitOfWork;
+use infrastructure::postgres::EventSourcedWalletRepository;
+use infrastructure::postgres::IdempotencyKeyRepository;
+use infrastructure::postgres::OutboxRelay;
+use infrastructure::postgres::QueryLedgerRepository;
+use infrastructure::postgres::QueryTransactionRepository;
 use infrastructure::postgres::QueryWalletRepository;
+use infrastructure::postgres::WalletProjector;
 use infrastructure::postgres::WalletRepository;
 use infrastructure::ulid::IdRepository;
 use interface::controller::WalletController;
+use interface::controller::WalletPorts;
+use interface::idempotency::{Idempotency, IdempotencyRepository};
+use query::interactor::GetTrialBalanceInteractor;
+use query::interactor::ListTransactionsInteractor;
 use query::interactor::ListWalletsInteractor;
-use std::env;
+use std::time::{Duration, SystemTime};
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::Interval;
+use tracing::{error, info, warn};
+use tracing_subscriber::filter::EnvFilter;
+use tracing_subscriber::fmt::format::FmtSpan;
 use usecase::interactor::CreateWalletInteractor;
 use usecase::interactor::DeleteWalletInteractor;
+use usecase::interactor::DepositWalletInteractor;
 use usecase::interactor::GetWalletInteractor;
+use usecase::interactor::PurgeWalletsInteractor;
+use usecase::interactor::TransferInteractor;
+use usecase::interactor::UndeleteWalletInteractor;
+use usecase::interactor::WithdrawWalletInteractor;
+use usecase::port::{Port, PurgeWalletsInputData};
+
+// soft-deleted wallets can be undeleted for this long before the purge job removes them
+const DELETED_WALLET_RETENTION: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+// retries carrying an idempotency key are answered from the stored response for this long
+const IDEMPOTENCY_KEY_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const RELAY_INTERVAL: Duration = Duration::from_secs(1);
+const RELAY_BATCH_SIZE: i64 = 100;
+const PROJECTION_INTERVAL: Duration = Duration::from_secs(1);
+const PROJECTION_BATCH_SIZE: i64 = 100;
+const HEALTH_PROBE_INTERVAL: Duration = Duration::from_secs(5);
+// a database answering slower than this counts as out of reach
+const HEALTH_PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+const TLS_RELOAD_INTERVAL: Duration = Duration::from_secs(10);
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let database_url = env::var("WALLET_DATABASE_URL").expect("WALLET_DATABASE_URL must be set");
+    let args = Args::parse();
+    // nothing can be logged before the log filter is known, so these go to stderr
+    let config = Config::load(&args).unwrap_or_else(|e| invalid_config(e));
+    let filter = EnvFilter::try_new(&config.log.filter)
+        .unwrap_or_else(|e| invalid_config(format!("log.filter: {}", e)));
+    let tls = config
+        .tls
+        .as_ref()
+        .map(|tls| Tls::load(tls).unwrap_or_else(|e| invalid_config(format!("tls: {}", e))));
+
+    // one JSON object per line
+    tracing_subscriber::fmt()
+        .json()
+        .with_env_filter(filter)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_current_span(true)
+        .with_span_list(true)
+        .init();
+
+    let connections = DbPool::with_options(&config.database.url, &config.pool_options());
 
-    let connections = DbPool::new(&database_url);
+    if let Some(Command::RebuildReadModel) = args.command {
+        connections.init()?;
+        let applied = WalletProjector::new(connections, PROJECTION_BATCH_SIZE)
+            .rebuild()
+            .await?;
+        info!(applied, "rebuilt the wallet read model");
 
+        return Ok(());
+    }
+
+    match config.features.store {
+        Store::State => run(config, tls, connections, WalletRepository::new).await,
+        Store::Events => run(config, tls, connections, EventSourcedWalletRepository::new).await,
+    }
+}
+
+fn invalid_config<E: std::fmt::Display>(e: E) -> ! {
+    eprintln!("invalid configuration: {}", e);
+    std::process::exit(2)
+}
+
+async fn run<R>(
+    config: Config,
+    tls: Option<Tls>,
+    connections: DbPool,
+    wallet_repository: fn(DbPool) -> R,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    R: CreateRepository<Wallet>
+        + GetRepository<Wallet>
+        + UpdateRepository<Wallet>
+        + TransferRepository<Wallet>
+        + DeleteRepository<Wallet>
+        + UndeleteRepository<Wallet>
+        + PurgeRepository<Wallet>
+        + Clone
+        + Send
+        + Sync
+        + 'static,
+{
     let id_repository = IdRepository::default();
+    let command_wallet_repository = wallet_repository(connections.clone());
+    // use cases making several repository calls make them in one transaction
+    let unit_of_work = DbUnitOfWork::new(connections.clone(), wallet_repository);
     let query_wallet_repository = QueryWalletRepository::new(connections.clone());
-    let command_wallet_repository = WalletRepository::new(connections.clone());
-    let create = CreateWalletInteractor::new(id_repository, command_wallet_repository.clone());
+    let query_transaction_repository = QueryTransactionRepository::new(connections.clone());
+    let query_ledger_repository = QueryLedgerRepository::new(connections.clone());
+    // subscribers react to wallet events in-process once the change is committed
+    let event_dispatcher = EventDispatcher::<WalletEvent>::new();
+    let metrics = Metrics::new();
+    event_dispatcher.subscribe(metrics.clone());
+    let event_publisher = AfterCommitPublisher::new(connections.clone(), event_dispatcher);
+    metrics.register_pool(connections.clone())?;
+    let create = CreateWalletInteractor::new(
+        id_repository.clone(),
+        unit_of_work.clone(),
+        event_publisher.clone(),
+    );
     let list = ListWalletsInteractor::new(query_wallet_repository.clone());
     let get = GetWalletInteractor::new(command_wallet_repository.clone());
-    let delete = DeleteWalletInteractor::new(command_wallet_repository.clone());
-    let controller = WalletController::new(create, list, get, delete);
-    let service = Service::new(controller);
+    let delete = DeleteWalletInteractor::new(unit_of_work.clone(), event_publisher.clone());
+    let deposit = DepositWalletInteractor::new(
+        id_repository.clone(),
+        unit_of_work.clone(),
+        event_publisher.clone(),
+    );
+    let withdraw = WithdrawWalletInteractor::new(
+        id_repository.clone(),
+        unit_of_work.clone(),
+        event_publisher.clone(),
+    );
+    let transfer = TransferInteractor::new(
+        TransferService::default(),
+        id_repository,
+        command_wallet_repository.clone(),
+        event_publisher.clone(),
+    );
+    let undelete =
+        UndeleteWalletInteractor::new(unit_of_work, event_publisher, DELETED_WALLET_RETENTION);
+    let purge =
+        PurgeWalletsInteractor::new(command_wallet_repository.clone(), DELETED_WALLET_RETENTION);
+    let list_transactions = ListTransactionsInteractor::new(query_transaction_repository);
+    let get_trial_balance = GetTrialBalanceInteractor::new(query_ledger_repository);
+    let controller = WalletController::new(WalletPorts {
+        create_wallet: create,
+        list_wallets: list,
+        get_wallet: get,
+        delete_wallet: delete,
+        deposit_wallet: deposit,
+        withdraw_wallet: withdraw,
+        transfer,
+        undelete_wallet: undelete,
+        list_transactions,
+        get_trial_balance,
+    });
+    let idempotency_repository = IdempotencyKeyRepository::new(connections.clone());
+    let health = Health::new();
+    let mut service = Service::new(
+        controller,
+        Idempotency::new(idempotency_repository.clone(), IDEMPOTENCY_KEY_TTL),
+        metrics.clone(),
+        health.clone(),
+    )
+    .reflection(config.features.reflection);
+    if let Some(tls) = &tls {
+        service = service.tls(tls.clone());
+    }
+
+    let addr = config.server.listen_addr;
+    match &tls {
+        Some(tls) => {
+            info!(%addr, client_certs = tls.verifies_clients(), "service listening on TLS")
+        }
+        None => info!(%addr, "service listening"),
+    }
+
+    // scraped by Prometheus apart from the gRPC traffic
+    if config.features.metrics {
+        let metrics_addr = config.server.metrics_addr;
+        info!(addr = %metrics_addr, "metrics listening");
+        tokio::spawn(async move {
+            if let Err(e) = metrics.serve(metrics_addr).await {
+                error!(error = %e, "failed to serve metrics");
+            }
+        });
+    }
+
+    // the deadline for the requests in flight once a shutdown is signalled
+    let shutdown_timeout = config.shutdown_timeout();
+    // flips to true when a shutdown is signalled, which stops the background workers
+    let (stop, stopped) = watch::channel(false);
+
+    // the service is up while the migrations run, its health reporting NOT_SERVING until they
+    // are done, so a slow migration is not mistaken for a dead process
+    let startup = {
+        let connections = connections.clone();
+        let health = health.clone();
+        let stopped = stopped.clone();
+        async move {
+            let init = connections.clone();
+            tokio::task::spawn_blocking(move || init.init()).await??;
+            // wallets the state store kept until the switch are found in the event store too
+            if let Store::Events = config.features.store {
+                let repository = EventSourcedWalletRepository::new(connections.clone());
+                let imported = repository.import().await?;
+                info!(imported, "imported wallets from the state store");
+            }
+            health.set_migrated();
+
+            let mut workers = vec![
+                spawn_health_probe(health, connections.clone(), stopped.clone()),
+                spawn_projector(
+                    WalletProjector::new(connections.clone(), PROJECTION_BATCH_SIZE),
+                    stopped.clone(),
+                ),
+                spawn_purge(purge, idempotency_repository, stopped.clone()),
+            ];
+
+            // without a broker the events wait in the outbox until one is configured
+            match config.broker.nats_addr {
+                Some(addr) => workers.push(spawn_relay(
+                    OutboxRelay::new(connections.clone(), NatsBroker::new(addr), RELAY_BATCH_SIZE),
+                    stopped,
+                )),
+                None => warn!("no NATS address is configured, wallet events are not relayed"),
+            }
+
+            Ok::<_, Box<dyn std::error::Error>>(workers)
+        }
+    };
+
+    // a renewed certificate is picked up without a restart
+    let tls_reload = tls.map(|tls| spawn_tls_reload(tls, stopped.clone()));
+
+    // load balancers see the health change while new connections are refused and the requests
+    // in flight finish, for as long as the deadline allows
+    let serving = {
+        let mut signalled = stopped.clone();
+        let signal = async move {
+            shutdown_signal().await;
+            info!(timeout_secs = shutdown_timeout.as_secs(), "shutting down");
+            health.shut_down();
+            let _ = stop.send(true);
+        };
+        let deadline = async move {
+            let _ = signalled.changed().await;
+            tokio::time::sleep(shutdown_timeout).await;
+        };
+        async move {
+            tokio::select! {
+                served = service.serve_with_shutdown(addr, signal) => served,
+                _ = deadline => {
+                    warn!("requests still in flight at the shutdown deadline were dropped");
+                    Ok(())
+                }
+            }
+        }
+    };
 
-    let addr = "0.0.0.0:50051".parse()?;
+    // the service stops if the migrations fail
+    let (workers, ()) = tokio::try_join!(startup, serving)?;
 
-    println!("service listening on {}", addr);
+    // a worker in the middle of a run finishes it first
+    for worker in workers.into_iter().chain(tls_reload) {
+        if let Err(e) = worker.await {
+            error!(error = ?e, "failed to stop a background worker");
+        }
+    }
 
-    connections.init()?;
-    service.serve(addr).await?;
+    if tokio::time::timeout(shutdown_timeout, connections.close())
+        .await
+        .is_err()
+    {
+        warn!("database calls still running at the shutdown deadline were dropped");
+    }
+
+    info!("shut down");
 
     Ok(())
 }
+
+/// Completes on SIGTERM, which Kubernetes sends to stop a pod, or on SIGINT.
+async fn shutdown_signal() {
+    let mut terminate = signal(SignalKind::terminate()).expect("failed to listen for SIGTERM");
+    tokio::select! {
+        _ = terminate.recv() => {}
+        _ = tokio::signal::ctrl_c() => {}
+    }
+}
+
+/// Waits for the next tick of `interval` and returns whether the worker should run again, which
+/// it should not once a shutdown has been signalled.
+async fn tick(interval: &mut Interval, stopped: &mut watch::Receiver<bool>) -> bool {
+    tokio::select! {
+        _ = interval.tick() => !*stopped.borrow(),
+        _ = stopped.changed() => false,
+    }
+}
+
+fn spawn_health_probe(
+    health: Health,
+    connections: DbPool,
+    mut stopped: watch::Receiver<bool>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(HEALTH_PROBE_INTERVAL);
+        while tick(&mut interval, &mut stopped).await {
+            let reachable =
+                match tokio::time::timeout(HEALTH_PROBE_TIMEOUT, connections.ping()).await {
+                    Ok(Ok(())) => true,
+                    Ok(Err(e)) => {
+                        warn!(error = %e, "failed to reach the database");
+                        false
+                    }
+                    Err(_) => {
+                        warn!("timed out reaching the database");
+                        false
+                    }
+                };
+            health.set_database(reachable);
+        }
+    })
+}
+
+fn spawn_tls_reload(tls: Tls, mut stopped: watch::Receiver<bool>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(TLS_RELOAD_INTERVAL);
+        // the certificate was just loaded
+        interval.tick().await;
+        while tick(&mut interval, &mut stopped).await {
+            let tls = tls.clone();
+            match tokio::task::spawn_blocking(move || tls.reload()).await {
+                Ok(Ok(true)) => info!("reloaded the TLS certificate"),
+                Ok(Ok(false)) => {}
+                Ok(Err(e)) => error!(error = %e, "failed to reload the TLS certificate"),
+                Err(e) => error!(error = ?e, "failed to reload the TLS certificate"),
+            }
+        }
+    })
+}
+
+fn spawn_purge<W, R>(
+    purge: PurgeWalletsInteractor<W>,
+    idempotency_repository: R,
+    mut stopped: watch::Receiver<bool>,
+) -> JoinHandle<()>
+where
+    W: PurgeRepository<Wallet> + Send + Sync + 'static,
+    R: IdempotencyRepository + Send + Sync + 'static,
+{
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PURGE_INTERVAL);
+        while tick(&mut interval, &mut stopped).await {
+            match purge.handle(PurgeWalletsInputData::new()).await {
+                Ok(output) => info!(purged = output.purged, "purged deleted wallets"),
+                Err(e) => error!(error = ?e, "failed to purge deleted wallets"),
+            }
+
+            match idempotency_repository.purge(SystemTime::now()).await {
+                Ok(purged) => info!(purged, "purged expired idempotency keys"),
+                Err(e) => error!(error = ?e, "failed to purge expired idempotency keys"),
+            }
+        }
+    })
+}
+
+fn spawn_relay<B>(relay: OutboxRelay<B>, mut stopped: watch::Receiver<bool>) -> JoinHandle<()>
+where
+    B: Broker + 'static,
+{
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(RELAY_INTERVAL);
+        while tick(&mut interval, &mut stopped).await {
+            if let Err(e) = relay.relay().await {
+                error!(error = ?e, "failed to relay wallet events");
+            }
+        }
+    })
+}
+
+fn spawn_projector(
+    projector: WalletProjector,
+    mut stopped: watch::Receiver<bool>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PROJECTION_INTERVAL);
+        while tick(&mut interval, &mut stopped).await {
+            if let Err(e) = projector.project().await {
+                error!(error = ?e, "failed to project wallet events");
+            }
+        }
+    })
+}
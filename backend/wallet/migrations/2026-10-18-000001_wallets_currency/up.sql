@@ -0,0 +1,2 @@
+ALTER TABLE wallets
+ADD COLUMN currency VARCHAR(3) NOT NULL DEFAULT 'JPY';
@@ -0,0 +1,3 @@
+UPDATE wallets
+SET id = '0123456789ABCDEFGHJKMNPQRSTVWXYZ'
+WHERE id = '01FK2Z1C0Q8ZMNJ5DNT6YBA9B5';
@@ -0,0 +1 @@
+DROP INDEX wallets_owner_id_idx;
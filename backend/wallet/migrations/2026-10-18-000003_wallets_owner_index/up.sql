@@ -0,0 +1 @@
+CREATE INDEX wallets_owner_id_idx ON wallets (owner, id);
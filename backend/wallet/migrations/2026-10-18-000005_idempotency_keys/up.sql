@@ -0,0 +1,8 @@
+CREATE TABLE idempotency_keys (
+    key VARCHAR(255) PRIMARY KEY,
+    request_hash VARCHAR(64) NOT NULL,
+    response BYTEA DEFAULT NULL,
+    create_at timestamptz NOT NULL DEFAULT current_timestamp,
+    expires_at timestamptz NOT NULL
+);
+CREATE INDEX idempotency_keys_expires_at_idx ON idempotency_keys (expires_at);
@@ -0,0 +1,13 @@
+-- the wallet is not a foreign key: wallets stored as events have no row in wallets
+CREATE TABLE wallet_transactions (
+    id VARCHAR(64) PRIMARY KEY,
+    wallet_id VARCHAR(64) NOT NULL,
+    kind VARCHAR(16) NOT NULL,
+    amount TEXT NOT NULL,
+    balance TEXT NOT NULL,
+    currency VARCHAR(3) NOT NULL,
+    counterparty VARCHAR(64) DEFAULT NULL,
+    memo TEXT NOT NULL DEFAULT '',
+    create_at timestamptz NOT NULL DEFAULT current_timestamp
+);
+CREATE INDEX wallet_transactions_wallet_id_id_idx ON wallet_transactions (wallet_id, id);
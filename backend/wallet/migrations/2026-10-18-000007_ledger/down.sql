@@ -0,0 +1,14 @@
+ALTER TABLE wallets
+ADD COLUMN balance TEXT NOT NULL DEFAULT '0';
+UPDATE wallets
+SET balance = (
+        SELECT COALESCE(- SUM(amount::numeric), 0)::text
+        FROM postings
+        WHERE account_id = 'wallet:' || wallets.id
+    );
+ALTER TABLE wallets
+ALTER COLUMN balance DROP DEFAULT;
+DROP TABLE postings;
+DROP FUNCTION ensure_journal_entry_balanced;
+DROP TABLE journal_entries;
+DROP TABLE ledger_accounts;
@@ -0,0 +1,72 @@
+CREATE TABLE ledger_accounts (
+    id VARCHAR(80) PRIMARY KEY,
+    kind VARCHAR(16) NOT NULL,
+    currency VARCHAR(3) NOT NULL,
+    create_at timestamptz NOT NULL DEFAULT current_timestamp,
+    UNIQUE (id, currency)
+);
+CREATE TABLE journal_entries (
+    id VARCHAR(64) PRIMARY KEY,
+    memo TEXT NOT NULL DEFAULT '',
+    create_at timestamptz NOT NULL DEFAULT current_timestamp
+);
+-- amounts are signed: debits are positive and credits negative
+CREATE TABLE postings (
+    id BIGSERIAL PRIMARY KEY,
+    entry_id VARCHAR(64) NOT NULL REFERENCES journal_entries (id),
+    account_id VARCHAR(80) NOT NULL,
+    currency VARCHAR(3) NOT NULL,
+    amount TEXT NOT NULL CHECK (amount::numeric <> 0),
+    FOREIGN KEY (account_id, currency) REFERENCES ledger_accounts (id, currency)
+);
+CREATE INDEX postings_account_id_idx ON postings (account_id);
+CREATE INDEX postings_entry_id_idx ON postings (entry_id);
+-- checked at commit, once every posting of the entry is in
+CREATE FUNCTION ensure_journal_entry_balanced() RETURNS TRIGGER AS $$ BEGIN IF EXISTS (
+    SELECT 1
+    FROM postings
+    WHERE entry_id = NEW.entry_id
+    GROUP BY currency
+    HAVING SUM(amount::numeric) <> 0
+) THEN RAISE EXCEPTION 'journal entry % does not balance',
+NEW.entry_id;
+END IF;
+RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+CREATE CONSTRAINT TRIGGER postings_balanced
+AFTER
+INSERT ON postings DEFERRABLE INITIALLY DEFERRED FOR EACH ROW EXECUTE PROCEDURE ensure_journal_entry_balanced();
+-- carry the existing balances over as opening entries against the funding accounts
+INSERT INTO ledger_accounts (id, kind, currency)
+SELECT 'wallet:' || id,
+    'LIABILITY',
+    currency
+FROM wallets;
+INSERT INTO ledger_accounts (id, kind, currency)
+SELECT DISTINCT 'funding:' || currency,
+    'ASSET',
+    currency
+FROM wallets
+WHERE balance::numeric <> 0;
+INSERT INTO journal_entries (id, memo, create_at)
+SELECT id,
+    'opening balance',
+    create_at
+FROM wallets
+WHERE balance::numeric <> 0;
+INSERT INTO postings (entry_id, account_id, currency, amount)
+SELECT id,
+    'funding:' || currency,
+    currency,
+    balance
+FROM wallets
+WHERE balance::numeric <> 0
+UNION ALL
+SELECT id,
+    'wallet:' || id,
+    currency,
+    (- balance::numeric)::text
+FROM wallets
+WHERE balance::numeric <> 0;
+ALTER TABLE wallets DROP COLUMN balance;
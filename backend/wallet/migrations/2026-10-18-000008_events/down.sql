@@ -0,0 +1,2 @@
+DROP TABLE events;
+DROP FUNCTION reject_event_update;
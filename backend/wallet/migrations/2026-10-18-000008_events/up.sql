@@ -0,0 +1,16 @@
+CREATE TABLE events (
+    stream_id VARCHAR(64) NOT NULL,
+    sequence BIGINT NOT NULL CHECK (sequence > 0),
+    stream_type VARCHAR(32) NOT NULL,
+    kind VARCHAR(32) NOT NULL,
+    payload JSONB NOT NULL DEFAULT '{}',
+    create_at timestamptz NOT NULL DEFAULT current_timestamp,
+    PRIMARY KEY (stream_id, sequence)
+);
+CREATE INDEX events_kind_create_at_idx ON events (kind, create_at);
+-- events are facts: they may be purged with their stream but never rewritten
+CREATE FUNCTION reject_event_update() RETURNS TRIGGER AS $$ BEGIN RAISE EXCEPTION 'events are append-only';
+END;
+$$ LANGUAGE plpgsql;
+CREATE TRIGGER events_append_only BEFORE
+UPDATE ON events FOR EACH ROW EXECUTE PROCEDURE reject_event_update();
@@ -0,0 +1,14 @@
+CREATE TABLE outbox (
+    id BIGSERIAL PRIMARY KEY,
+    aggregate_type VARCHAR(32) NOT NULL,
+    aggregate_id VARCHAR(64) NOT NULL,
+    kind VARCHAR(32) NOT NULL,
+    payload JSONB NOT NULL DEFAULT '{}',
+    create_at timestamptz NOT NULL DEFAULT current_timestamp,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at timestamptz NOT NULL DEFAULT current_timestamp,
+    last_error TEXT DEFAULT NULL,
+    sent_at timestamptz DEFAULT NULL
+);
+-- the relay only ever scans the rows it has not sent yet
+CREATE INDEX outbox_pending_idx ON outbox (id) WHERE sent_at IS NULL;
@@ -0,0 +1,6 @@
+DROP TABLE wallet_views;
+DELETE FROM outbox
+WHERE id < 0;
+DROP INDEX outbox_unprojected_idx;
+ALTER TABLE outbox DROP COLUMN projected_at,
+    DROP COLUMN version;
@@ -0,0 +1,171 @@
+-- the version of the wallet once the event is applied, so the read model can hand out etags
+ALTER TABLE outbox
+ADD COLUMN version BIGINT NOT NULL DEFAULT 1,
+    ADD COLUMN projected_at timestamptz DEFAULT NULL;
+-- the rows already queued get the version their wallet has now, so the read model ends on the
+-- right one whatever comes after; only a creation is known to leave a wallet at version 1
+UPDATE outbox o
+SET version = COALESCE(
+        (
+            SELECT w.version
+            FROM wallets w
+            WHERE w.id = o.aggregate_id
+        ),
+        (
+            SELECT MAX(e.sequence)
+            FROM events e
+            WHERE e.stream_id = o.aggregate_id
+                AND e.stream_type = 'wallet'
+        ),
+        o.version
+    )
+WHERE o.kind <> 'WalletCreated';
+ALTER TABLE outbox
+ALTER COLUMN version DROP DEFAULT;
+CREATE INDEX outbox_unprojected_idx ON outbox (id)
+WHERE projected_at IS NULL;
+CREATE TABLE wallet_views (
+    id VARCHAR(64) PRIMARY KEY,
+    owner VARCHAR(64) NOT NULL,
+    currency VARCHAR(3) NOT NULL,
+    balance TEXT NOT NULL DEFAULT '0',
+    status VARCHAR(16) NOT NULL DEFAULT 'ACTIVE',
+    version BIGINT NOT NULL,
+    transaction_count BIGINT NOT NULL DEFAULT 0,
+    create_at timestamptz NOT NULL,
+    last_activity_at timestamptz NOT NULL
+);
+CREATE INDEX wallet_views_owner_id_idx ON wallet_views (owner, id);
+-- The outbox only holds what happened since it was added. Whatever happened before is written
+-- into it as already sent events, numbered below every existing row so that each wallet's
+-- history comes first, and the read model is projected from it like from any other event.
+WITH first_events AS (
+    SELECT aggregate_id,
+        MIN(create_at) AS create_at,
+        MIN(version) AS version,
+        bool_or(kind = 'WalletCreated') AS created,
+        bool_or(kind = 'WalletDeleted') AS deleted
+    FROM outbox
+    GROUP BY aggregate_id
+),
+history AS (
+    SELECT w.id AS aggregate_id,
+        'WalletCreated' AS kind,
+        json_build_object('owner', w.owner, 'currency', w.currency)::jsonb AS payload,
+        w.create_at,
+        w.version,
+        0 AS ord,
+        '' AS seq
+    FROM wallets w
+        LEFT JOIN first_events f ON f.aggregate_id = w.id
+    WHERE f.created IS NOT TRUE
+    UNION ALL
+    -- balances from before the ledger were carried over as entries named after the wallet
+    SELECT w.id,
+        'WalletDeposited',
+        json_build_object(
+            'transaction_id',
+            w.id,
+            'amount',
+            (- p.amount::numeric)::text,
+            'currency',
+            p.currency,
+            'counterparty',
+            NULL,
+            'memo',
+            e.memo
+        )::jsonb,
+        e.create_at,
+        w.version,
+        1,
+        ''
+    FROM wallets w
+        JOIN journal_entries e ON e.id = w.id
+        JOIN postings p ON p.entry_id = e.id
+        AND p.account_id = 'wallet:' || w.id
+        LEFT JOIN first_events f ON f.aggregate_id = w.id
+    WHERE f.created IS NOT TRUE
+    UNION ALL
+    SELECT t.wallet_id,
+        CASE
+            WHEN t.kind IN ('DEPOSIT', 'TRANSFER_IN') THEN 'WalletDeposited'
+            ELSE 'WalletWithdrawn'
+        END,
+        json_build_object(
+            'transaction_id',
+            t.id,
+            'amount',
+            t.amount,
+            'currency',
+            t.currency,
+            'counterparty',
+            t.counterparty,
+            'memo',
+            t.memo
+        )::jsonb,
+        t.create_at,
+        w.version,
+        2,
+        t.id
+    FROM wallet_transactions t
+        JOIN wallets w ON w.id = t.wallet_id
+    WHERE NOT EXISTS (
+            SELECT 1
+            FROM outbox o
+            WHERE o.aggregate_id = t.wallet_id
+                AND o.payload->>'transaction_id' = t.id
+        )
+    UNION ALL
+    SELECT w.id,
+        'WalletDeleted',
+        '{}'::jsonb,
+        w.delete_at,
+        w.version,
+        3,
+        ''
+    FROM wallets w
+        LEFT JOIN first_events f ON f.aggregate_id = w.id
+    WHERE w.delete_at IS NOT NULL
+        AND f.deleted IS NOT TRUE
+    UNION ALL
+    SELECT e.stream_id,
+        e.kind,
+        e.payload,
+        e.create_at,
+        e.sequence,
+        2,
+        lpad(e.sequence::text, 20, '0')
+    FROM events e
+        LEFT JOIN first_events f ON f.aggregate_id = e.stream_id
+    WHERE e.stream_type = 'wallet'
+        AND (
+            f.create_at IS NULL
+            OR e.create_at < f.create_at
+        )
+)
+INSERT INTO outbox (
+        id,
+        aggregate_type,
+        aggregate_id,
+        kind,
+        payload,
+        create_at,
+        version,
+        sent_at,
+        projected_at
+    )
+SELECT ROW_NUMBER() OVER (
+        ORDER BY h.aggregate_id,
+            h.ord,
+            h.seq
+    ) - COUNT(*) OVER () - 1,
+    'wallet',
+    h.aggregate_id,
+    h.kind,
+    h.payload,
+    h.create_at,
+    LEAST(h.version, COALESCE(f.version - 1, h.version)),
+    current_timestamp,
+    NULL
+FROM history h
+    LEFT JOIN first_events f ON f.aggregate_id = h.aggregate_id;
@@ -0,0 +1,158 @@
+use crate::port::{GetTrialBalanceInputData, GetTrialBalanceOutputData, QueryPort};
+use crate::repository::TrialBalanceRepository;
+use crate::view::{LedgerAccount, TrialBalanceTotal};
+use async_trait::async_trait;
+use derive_new::new;
+use domain::error::{Error, Result};
+use domain::ledger::TrialBalance;
+use domain::vo::Currency;
+
+#[derive(new)]
+pub struct GetTrialBalanceInteractor<Query>
+where
+    Query: TrialBalanceRepository<TrialBalance>,
+{
+    ledger_repository: Query,
+}
+
+#[async_trait]
+impl<Query> QueryPort<GetTrialBalanceInputData, GetTrialBalanceOutputData>
+    for GetTrialBalanceInteractor<Query>
+where
+    Query: TrialBalanceRepository<TrialBalance>,
+{
+    async fn handle(
+        &self,
+        input: GetTrialBalanceInputData,
+    ) -> Result<GetTrialBalanceOutputData, Error> {
+        let currency = match input.currency.as_str() {
+            "" => None,
+            currency => Some(
+                currency
+                    .parse::<Currency>()
+                    .map_err(|e| Error::invalid_argument("currency", e))?,
+            ),
+        };
+
+        let trial_balance = self.ledger_repository.trial_balance(currency).await?;
+
+        let accounts = trial_balance
+            .lines()
+            .iter()
+            .map(|l| {
+                Ok(LedgerAccount {
+                    account: l.account().to_string(),
+                    kind: l.account().kind().to_string(),
+                    currency: l.currency().to_string(),
+                    debit: l.debit().amount().to_string(),
+                    credit: l.credit().amount().to_string(),
+                    balance: l.balance()?.amount().to_string(),
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        let totals = trial_balance
+            .totals()
+            .iter()
+            .map(|t| TrialBalanceTotal {
+                currency: t.currency().to_string(),
+                debit: t.debit().amount().to_string(),
+                credit: t.credit().amount().to_string(),
+                balanced: t.is_balanced(),
+            })
+            .collect();
+
+        Ok(GetTrialBalanceOutputData::new(
+            accounts,
+            totals,
+            trial_balance.is_balanced(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use domain::ledger::TrialBalanceLine;
+
+    struct MockLedgerRepository {
+        lines: Vec<TrialBalanceLine>,
+    }
+    impl MockLedgerRepository {
+        fn new(lines: &[(&str, &str, &str)]) -> Self {
+            Self {
+                lines: lines
+                    .iter()
+                    .map(|(account, debit, credit)| {
+                        TrialBalanceLine::new(
+                            account.parse().unwrap(),
+                            debit.parse().unwrap(),
+                            credit.parse().unwrap(),
+                        )
+                        .unwrap()
+                    })
+                    .collect(),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl TrialBalanceRepository<TrialBalance> for MockLedgerRepository {
+        async fn trial_balance(&self, currency: Option<Currency>) -> Result<TrialBalance, Error> {
+            Ok(TrialBalance::new(
+                self.lines
+                    .iter()
+                    .filter(|l| currency.iter().all(|c| &l.currency() == c))
+                    .cloned()
+                    .collect(),
+            )?)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_trial_balance_handle() {
+        let sut = GetTrialBalanceInteractor::new(MockLedgerRepository::new(&[
+            ("funding:JPY", "1500 JPY", "200 JPY"),
+            ("wallet:01F8MECHZX3TBDSZ7XRADM79XE", "200 JPY", "1500 JPY"),
+            ("funding:USD", "10.00 USD", "0.00 USD"),
+            ("wallet:01F8MECHZX3TBDSZ7XRADM79XF", "0.00 USD", "9.00 USD"),
+        ]));
+
+        let output = sut
+            .handle(GetTrialBalanceInputData::new("JPY".to_string()))
+            .await
+            .unwrap();
+        assert!(output.balanced);
+        assert_eq!(output.accounts.len(), 2);
+        assert_eq!(output.accounts[1].kind, "LIABILITY".to_string());
+        assert_eq!(output.accounts[1].balance, "1300".to_string());
+        assert_eq!(
+            output.totals,
+            vec![TrialBalanceTotal {
+                currency: "JPY".to_string(),
+                debit: "1700".to_string(),
+                credit: "1700".to_string(),
+                balanced: true,
+            }]
+        );
+
+        // the USD books are off
+        let output = sut
+            .handle(GetTrialBalanceInputData::new("".to_string()))
+            .await
+            .unwrap();
+        assert!(!output.balanced);
+        assert_eq!(output.totals.len(), 2);
+        assert!(!output.totals[1].balanced);
+
+        // err
+        assert!(matches!(
+            sut.handle(GetTrialBalanceInputData::new("XXX".to_string()))
+                .await
+                .unwrap_err(),
+            Error::InvalidArgument {
+                field: "currency",
+                ..
+            }
+        ));
+    }
+}
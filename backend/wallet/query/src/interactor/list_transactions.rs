@@ -0,0 +1,275 @@
+use crate::interactor::page::{decode_page_token, encode_page_token, page_size};
+use crate::port::{
+    ListTransactionsError, ListTransactionsInputData, ListTransactionsOutputData, QueryPort,
+};
+use crate::repository::{ListTransactionsRepository, TransactionCriteria};
+use crate::view::Transaction;
+use async_trait::async_trait;
+use derive_new::new;
+use domain::entity::Wallet;
+use domain::error::{Error, Result};
+use domain::vo::Id;
+
+#[derive(new)]
+pub struct ListTransactionsInteractor<Query>
+where
+    Query: ListTransactionsRepository<Transaction>,
+{
+    transaction_repository: Query,
+}
+
+#[async_trait]
+impl<Query> QueryPort<ListTransactionsInputData, ListTransactionsOutputData>
+    for ListTransactionsInteractor<Query>
+where
+    Query: ListTransactionsRepository<Transaction>,
+{
+    async fn handle(
+        &self,
+        input: ListTransactionsInputData,
+    ) -> Result<ListTransactionsOutputData, Error> {
+        let wallet_id = input
+            .wallet_id
+            .parse::<Id<Wallet>>()
+            .map_err(|e| Error::invalid_argument("wallet_id", e))?;
+        let page_size = page_size(input.page_size)
+            .ok_or(ListTransactionsError::InvalidPageSize(input.page_size))?;
+        let after = match input.page_token.as_str() {
+            "" => None,
+            token => Some(decode_page_token(token).ok_or(ListTransactionsError::InvalidPageToken)?),
+        };
+        if let (Some(start_time), Some(end_time)) = (input.start_time, input.end_time) {
+            if start_time >= end_time {
+                return Err(ListTransactionsError::InvalidTimeRange.into());
+            }
+        }
+
+        // fetch one extra row to find out whether another page follows
+        let mut page = self
+            .transaction_repository
+            .list_transactions(TransactionCriteria::new(
+                wallet_id.to_string(),
+                after,
+                page_size + 1,
+                input.start_time,
+                input.end_time,
+            ))
+            .await?;
+
+        let next_page_token = if page.items.len() as i64 > page_size {
+            page.items.truncate(page_size as usize);
+            page.items
+                .last()
+                .map(|t| encode_page_token(&t.id))
+                .unwrap_or_default()
+        } else {
+            "".to_string()
+        };
+
+        Ok(ListTransactionsOutputData::new(
+            page.items,
+            next_page_token,
+            page.total_size,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::Page;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    const WALLET_ID: &str = "01F8MECHZX3TBDSZ7XRADM79XE";
+
+    struct MockTransactionRepository {
+        store: Vec<Transaction>,
+    }
+    impl MockTransactionRepository {
+        fn new() -> Self {
+            let transaction = |id: &str, wallet_id: &str, seconds: u64| Transaction {
+                id: id.to_string(),
+                wallet_id: wallet_id.to_string(),
+                kind: "DEPOSIT".to_string(),
+                amount: "100".to_string(),
+                balance: "100".to_string(),
+                currency: "JPY".to_string(),
+                counterparty: None,
+                memo: "".to_string(),
+                create_at: UNIX_EPOCH + Duration::from_secs(seconds),
+            };
+
+            Self {
+                store: vec![
+                    transaction("01F8MECHZX3TBDSZ7XRADM79XA", WALLET_ID, 10),
+                    transaction("01F8MECHZX3TBDSZ7XRADM79XB", WALLET_ID, 20),
+                    transaction(
+                        "01F8MECHZX3TBDSZ7XRADM79XC",
+                        "01F8MECHZX3TBDSZ7XRADM79XF",
+                        30,
+                    ),
+                    transaction("01F8MECHZX3TBDSZ7XRADM79XD", WALLET_ID, 40),
+                ],
+            }
+        }
+    }
+
+    #[async_trait]
+    impl ListTransactionsRepository<Transaction> for MockTransactionRepository {
+        async fn list_transactions(
+            &self,
+            criteria: TransactionCriteria,
+        ) -> Result<Page<Transaction>, Error> {
+            let filtered = self
+                .store
+                .iter()
+                .filter(|t| t.wallet_id == criteria.wallet_id)
+                .filter(|t| criteria.start_time.iter().all(|s| &t.create_at >= s))
+                .filter(|t| criteria.end_time.iter().all(|e| &t.create_at < e))
+                .cloned()
+                .collect::<Vec<_>>();
+            let total_size = filtered.len() as i64;
+
+            Ok(Page::new(
+                filtered
+                    .into_iter()
+                    .filter(|t| criteria.after.iter().all(|a| &t.id > a))
+                    .take(criteria.limit as usize)
+                    .collect(),
+                total_size,
+            ))
+        }
+    }
+
+    fn at(seconds: u64) -> Option<SystemTime> {
+        Some(UNIX_EPOCH + Duration::from_secs(seconds))
+    }
+
+    #[tokio::test]
+    async fn test_list_transactions_handle() {
+        let sut = ListTransactionsInteractor::new(MockTransactionRepository::new());
+
+        let output = sut
+            .handle(ListTransactionsInputData::new(
+                WALLET_ID.to_string(),
+                0,
+                "".to_string(),
+                None,
+                None,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(output.transactions.len(), 3);
+        assert_eq!(output.next_page_token, "".to_string());
+        assert_eq!(output.total_size, 3);
+
+        // time range
+        let output = sut
+            .handle(ListTransactionsInputData::new(
+                WALLET_ID.to_string(),
+                0,
+                "".to_string(),
+                at(20),
+                at(40),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(
+            output
+                .transactions
+                .iter()
+                .map(|t| t.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["01F8MECHZX3TBDSZ7XRADM79XB"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_transactions_handle_paging() {
+        let sut = ListTransactionsInteractor::new(MockTransactionRepository::new());
+
+        let first = sut
+            .handle(ListTransactionsInputData::new(
+                WALLET_ID.to_string(),
+                2,
+                "".to_string(),
+                None,
+                None,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(first.transactions.len(), 2);
+        assert_ne!(first.next_page_token, "".to_string());
+
+        let second = sut
+            .handle(ListTransactionsInputData::new(
+                WALLET_ID.to_string(),
+                2,
+                first.next_page_token,
+                None,
+                None,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(
+            second
+                .transactions
+                .iter()
+                .map(|t| t.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["01F8MECHZX3TBDSZ7XRADM79XD"]
+        );
+        assert_eq!(second.next_page_token, "".to_string());
+    }
+
+    #[tokio::test]
+    async fn test_list_transactions_handle_err() {
+        let sut = ListTransactionsInteractor::new(MockTransactionRepository::new());
+
+        assert!(matches!(
+            sut.handle(ListTransactionsInputData::new(
+                "hogehoge".to_string(),
+                0,
+                "".to_string(),
+                None,
+                None
+            ))
+            .await
+            .unwrap_err(),
+            Error::InvalidArgument {
+                field: "wallet_id",
+                ..
+            }
+        ));
+        assert!(matches!(
+            sut.handle(ListTransactionsInputData::new(
+                WALLET_ID.to_string(),
+                -1,
+                "".to_string(),
+                None,
+                None
+            ))
+            .await
+            .unwrap_err(),
+            Error::InvalidArgument {
+                field: "page_size",
+                ..
+            }
+        ));
+        assert!(matches!(
+            sut.handle(ListTransactionsInputData::new(
+                WALLET_ID.to_string(),
+                0,
+                "".to_string(),
+                at(40),
+                at(20)
+            ))
+            .await
+            .unwrap_err(),
+            Error::InvalidArgument {
+                field: "end_time",
+                ..
+            }
+        ));
+    }
+}
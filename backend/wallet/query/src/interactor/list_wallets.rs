@@ -1,8 +1,10 @@
-use crate::port::{ListWalletsInputData, ListWalletsOutputData, QueryPort};
-use crate::repository::ListRepository;
+use crate::interactor::page::{decode_page_token, encode_page_token, page_size};
+use crate::port::{ListWalletsError, ListWalletsInputData, ListWalletsOutputData, QueryPort};
+use crate::repository::{ListCriteria, ListRepository};
 use crate::view::Wallet;
-use anyhow::{Error, Result};
+use async_trait::async_trait;
 use derive_new::new;
+use domain::error::{Error, Result};
 
 #[derive(new)]
 pub struct ListWalletsInteractor<Query>
@@ -12,13 +14,221 @@ where
     wallet_repository: Query,
 }
 
+#[async_trait]
 impl<Query> QueryPort<ListWalletsInputData, ListWalletsOutputData> for ListWalletsInteractor<Query>
 where
     Query: ListRepository<Wallet>,
 {
-    fn handle(&self, _input: ListWalletsInputData) -> Result<ListWalletsOutputData, Error> {
-        let wallets = self.wallet_repository.list()?;
+    async fn handle(&self, input: ListWalletsInputData) -> Result<ListWalletsOutputData, Error> {
+        let page_size =
+            page_size(input.page_size).ok_or(ListWalletsError::InvalidPageSize(input.page_size))?;
+        let owner = match input.owner.as_str() {
+            "" => None,
+            owner => Some(owner.to_string()),
+        };
+        let after = match input.page_token.as_str() {
+            "" => None,
+            token => Some(decode_page_token(token).ok_or(ListWalletsError::InvalidPageToken)?),
+        };
 
-        Ok(ListWalletsOutputData::new(wallets))
+        // fetch one extra row to find out whether another page follows
+        let mut page = self
+            .wallet_repository
+            .list(ListCriteria::new(
+                owner,
+                after,
+                page_size + 1,
+                input.show_deleted,
+            ))
+            .await?;
+
+        let next_page_token = if page.items.len() as i64 > page_size {
+            page.items.truncate(page_size as usize);
+            page.items
+                .last()
+                .map(|w| encode_page_token(&w.id))
+                .unwrap_or_default()
+        } else {
+            "".to_string()
+        };
+
+        Ok(ListWalletsOutputData::new(
+            page.items,
+            next_page_token,
+            page.total_size,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::Page;
+    use std::time::SystemTime;
+
+    struct MockWalletRepository {
+        store: Vec<Wallet>,
+    }
+    impl MockWalletRepository {
+        fn new() -> Self {
+            let wallet = |id: &str, owner: &str, status: &str| Wallet {
+                id: id.to_string(),
+                owner: owner.to_string(),
+                balance: "0".to_string(),
+                currency: "JPY".to_string(),
+                version: 1,
+                status: status.to_string(),
+                transaction_count: 0,
+                last_activity_at: SystemTime::UNIX_EPOCH,
+            };
+
+            Self {
+                store: vec![
+                    wallet("01F8MECHZX3TBDSZ7XRADM79XA", "alice", "ACTIVE"),
+                    wallet("01F8MECHZX3TBDSZ7XRADM79XB", "bob", "ACTIVE"),
+                    wallet("01F8MECHZX3TBDSZ7XRADM79XC", "alice", "ACTIVE"),
+                    wallet("01F8MECHZX3TBDSZ7XRADM79XD", "alice", "ACTIVE"),
+                    wallet("01F8MECHZX3TBDSZ7XRADM79XE", "alice", "DELETED"),
+                ],
+            }
+        }
+    }
+
+    #[async_trait]
+    impl ListRepository<Wallet> for MockWalletRepository {
+        async fn list(&self, criteria: ListCriteria) -> Result<Page<Wallet>, Error> {
+            let filtered = self
+                .store
+                .iter()
+                .filter(|w| criteria.owner.iter().all(|o| &w.owner == o))
+                .filter(|w| criteria.show_deleted || w.status != "DELETED")
+                .cloned()
+                .collect::<Vec<_>>();
+            let total_size = filtered.len() as i64;
+
+            Ok(Page::new(
+                filtered
+                    .into_iter()
+                    .filter(|w| criteria.after.iter().all(|a| &w.id > a))
+                    .take(criteria.limit as usize)
+                    .collect(),
+                total_size,
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_wallets_handle() {
+        let sut = ListWalletsInteractor::new(MockWalletRepository::new());
+
+        let output = sut
+            .handle(ListWalletsInputData::new(
+                "".to_string(),
+                0,
+                "".to_string(),
+                false,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(output.wallets.len(), 4);
+        assert_eq!(output.next_page_token, "".to_string());
+        assert_eq!(output.total_size, 4);
+    }
+
+    #[tokio::test]
+    async fn test_list_wallets_handle_show_deleted() {
+        let sut = ListWalletsInteractor::new(MockWalletRepository::new());
+
+        let output = sut
+            .handle(ListWalletsInputData::new(
+                "alice".to_string(),
+                0,
+                "".to_string(),
+                true,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(output.wallets.len(), 4);
+        assert_eq!(output.wallets.last().unwrap().status, "DELETED");
+        assert_eq!(output.total_size, 4);
+    }
+
+    #[tokio::test]
+    async fn test_list_wallets_handle_paging() {
+        let sut = ListWalletsInteractor::new(MockWalletRepository::new());
+
+        let first = sut
+            .handle(ListWalletsInputData::new(
+                "alice".to_string(),
+                2,
+                "".to_string(),
+                false,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(
+            first
+                .wallets
+                .iter()
+                .map(|w| w.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["01F8MECHZX3TBDSZ7XRADM79XA", "01F8MECHZX3TBDSZ7XRADM79XC"]
+        );
+        assert_ne!(first.next_page_token, "".to_string());
+        assert_eq!(first.total_size, 3);
+
+        let second = sut
+            .handle(ListWalletsInputData::new(
+                "alice".to_string(),
+                2,
+                first.next_page_token,
+                false,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(
+            second
+                .wallets
+                .iter()
+                .map(|w| w.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["01F8MECHZX3TBDSZ7XRADM79XD"]
+        );
+        assert_eq!(second.next_page_token, "".to_string());
+        assert_eq!(second.total_size, 3);
+    }
+
+    #[tokio::test]
+    async fn test_list_wallets_handle_err() {
+        let sut = ListWalletsInteractor::new(MockWalletRepository::new());
+
+        assert!(matches!(
+            sut.handle(ListWalletsInputData::new(
+                "".to_string(),
+                -1,
+                "".to_string(),
+                false
+            ))
+            .await
+            .unwrap_err(),
+            Error::InvalidArgument {
+                field: "page_size",
+                ..
+            }
+        ));
+        assert!(matches!(
+            sut.handle(ListWalletsInputData::new(
+                "".to_string(),
+                0,
+                "zz".to_string(),
+                false
+            ))
+            .await
+            .unwrap_err(),
+            Error::InvalidArgument {
+                field: "page_token",
+                ..
+            }
+        ));
     }
 }
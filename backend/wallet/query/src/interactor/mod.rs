@@ -1,3 +1,8 @@
+mod get_trial_balance;
+mod list_transactions;
 mod list_wallets;
+mod page;
 
+pub use get_trial_balance::*;
+pub use list_transactions::*;
 pub use list_wallets::*;
@@ -0,0 +1,55 @@
+pub(crate) const DEFAULT_PAGE_SIZE: i64 = 50;
+pub(crate) const MAX_PAGE_SIZE: i64 = 1000;
+
+/// Resolves a requested page size, where 0 asks for the default. Negative sizes are rejected.
+pub(crate) fn page_size(requested: i32) -> Option<i64> {
+    match requested {
+        0 => Some(DEFAULT_PAGE_SIZE),
+        n if n < 0 => None,
+        n => Some(i64::from(n).min(MAX_PAGE_SIZE)),
+    }
+}
+
+// page tokens are the hex encoded id of the last item on the previous page, so clients treat
+// them as opaque while the repository can seek past that id
+pub(crate) fn encode_page_token(id: &str) -> String {
+    id.bytes().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub(crate) fn decode_page_token(token: &str) -> Option<String> {
+    let bytes = token
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| match std::str::from_utf8(pair) {
+            Ok(hex) if hex.len() == 2 => u8::from_str_radix(hex, 16).ok(),
+            _ => None,
+        })
+        .collect::<Option<Vec<u8>>>()?;
+
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_size() {
+        assert_eq!(page_size(0), Some(DEFAULT_PAGE_SIZE));
+        assert_eq!(page_size(10), Some(10));
+        assert_eq!(page_size(5000), Some(MAX_PAGE_SIZE));
+        assert_eq!(page_size(-1), None);
+    }
+
+    #[test]
+    fn test_page_token() {
+        let token = encode_page_token("01F8MECHZX3TBDSZ7XRADM79XE");
+
+        assert_eq!(
+            decode_page_token(&token).unwrap(),
+            "01F8MECHZX3TBDSZ7XRADM79XE".to_string()
+        );
+        assert!(decode_page_token("abc").is_none());
+        assert!(decode_page_token("ff").is_none());
+    }
+}
@@ -0,0 +1,20 @@
+use crate::port::{InputData, OutputData};
+use crate::view::{LedgerAccount, TrialBalanceTotal};
+use derive_new::new;
+
+#[derive(new, Clone, Debug, PartialEq)]
+pub struct GetTrialBalanceInputData {
+    pub currency: String,
+}
+
+impl InputData for GetTrialBalanceInputData {}
+
+#[derive(new, Clone, Debug, PartialEq)]
+pub struct GetTrialBalanceOutputData {
+    pub accounts: Vec<LedgerAccount>,
+    pub totals: Vec<TrialBalanceTotal>,
+    /// Whether debits equal credits in every currency.
+    pub balanced: bool,
+}
+
+impl OutputData for GetTrialBalanceOutputData {}
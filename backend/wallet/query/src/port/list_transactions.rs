@@ -0,0 +1,46 @@
+use crate::port::{InputData, OutputData};
+use crate::view::Transaction;
+use derive_new::new;
+use domain::error::Error;
+use std::time::SystemTime;
+use thiserror::Error;
+
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum ListTransactionsError {
+    #[error("page_size must not be negative, got {0}")]
+    InvalidPageSize(i32),
+    #[error("page_token is invalid")]
+    InvalidPageToken,
+    #[error("start_time must be before end_time")]
+    InvalidTimeRange,
+}
+
+impl From<ListTransactionsError> for Error {
+    fn from(e: ListTransactionsError) -> Self {
+        match e {
+            ListTransactionsError::InvalidPageSize(_) => Self::invalid_argument("page_size", e),
+            ListTransactionsError::InvalidPageToken => Self::invalid_argument("page_token", e),
+            ListTransactionsError::InvalidTimeRange => Self::invalid_argument("end_time", e),
+        }
+    }
+}
+
+#[derive(new, Clone, Debug, PartialEq)]
+pub struct ListTransactionsInputData {
+    pub wallet_id: String,
+    pub page_size: i32,
+    pub page_token: String,
+    pub start_time: Option<SystemTime>,
+    pub end_time: Option<SystemTime>,
+}
+
+impl InputData for ListTransactionsInputData {}
+
+#[derive(new, Clone, Debug, PartialEq)]
+pub struct ListTransactionsOutputData {
+    pub transactions: Vec<Transaction>,
+    pub next_page_token: String,
+    pub total_size: i64,
+}
+
+impl OutputData for ListTransactionsOutputData {}
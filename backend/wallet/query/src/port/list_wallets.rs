@@ -1,17 +1,41 @@
 use crate::port::{InputData, OutputData};
 use crate::view::Wallet;
 use derive_new::new;
+use domain::error::Error;
+use thiserror::Error;
+
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum ListWalletsError {
+    #[error("page_size must not be negative, got {0}")]
+    InvalidPageSize(i32),
+    #[error("page_token is invalid")]
+    InvalidPageToken,
+}
+
+impl From<ListWalletsError> for Error {
+    fn from(e: ListWalletsError) -> Self {
+        match e {
+            ListWalletsError::InvalidPageSize(_) => Self::invalid_argument("page_size", e),
+            ListWalletsError::InvalidPageToken => Self::invalid_argument("page_token", e),
+        }
+    }
+}
 
 #[derive(new, Clone, Debug, PartialEq)]
 pub struct ListWalletsInputData {
     pub owner: String,
+    pub page_size: i32,
+    pub page_token: String,
+    pub show_deleted: bool,
 }
 
 impl InputData for ListWalletsInputData {}
 
-#[derive(new, Clone, Debug)]
+#[derive(new, Clone, Debug, PartialEq)]
 pub struct ListWalletsOutputData {
     pub wallets: Vec<Wallet>,
+    pub next_page_token: String,
+    pub total_size: i64,
 }
 
 impl OutputData for ListWalletsOutputData {}
@@ -1,5 +1,9 @@
+mod get_trial_balance;
+mod list_transactions;
 mod list_wallets;
 mod query_port;
 
+pub use self::get_trial_balance::*;
+pub use self::list_transactions::*;
 pub use self::list_wallets::*;
 pub use self::query_port::*;
@@ -1,9 +1,15 @@
-use anyhow::{Error, Result};
+use async_trait::async_trait;
+use domain::error::{Error, Result};
 
 pub trait InputData {}
 pub trait OutputData {}
 
 #[mockall::automock]
-pub trait QueryPort<Input: InputData, Output: OutputData> {
-    fn handle(&self, input: Input) -> Result<Output, Error>;
+#[async_trait]
+pub trait QueryPort<Input, Output>: Send + Sync
+where
+    Input: InputData + Send + Sync + 'static,
+    Output: OutputData + Send + Sync + 'static,
+{
+    async fn handle(&self, input: Input) -> Result<Output, Error>;
 }
@@ -0,0 +1,12 @@
+use async_trait::async_trait;
+use domain::error::{Error, Result};
+use domain::vo::Currency;
+
+#[async_trait]
+pub trait TrialBalanceRepository<T>: Send + Sync
+where
+    T: Send + 'static,
+{
+    /// Sums up every account of the ledger, or only those in `currency` when set.
+    async fn trial_balance(&self, currency: Option<Currency>) -> Result<T, Error>;
+}
@@ -1,3 +1,7 @@
+mod ledger;
+mod transaction;
 mod wallet;
 
+pub use ledger::*;
+pub use transaction::*;
 pub use wallet::*;
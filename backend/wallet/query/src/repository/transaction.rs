@@ -0,0 +1,24 @@
+use crate::repository::Page;
+use async_trait::async_trait;
+use derive_new::new;
+use domain::error::{Error, Result};
+use std::time::SystemTime;
+
+#[derive(new, Clone, Debug, PartialEq)]
+pub struct TransactionCriteria {
+    pub wallet_id: String,
+    pub after: Option<String>,
+    pub limit: i64,
+    /// Only transactions at or after this time are listed when set.
+    pub start_time: Option<SystemTime>,
+    /// Only transactions before this time are listed when set.
+    pub end_time: Option<SystemTime>,
+}
+
+#[async_trait]
+pub trait ListTransactionsRepository<T>: Send + Sync
+where
+    T: Send + 'static,
+{
+    async fn list_transactions(&self, criteria: TransactionCriteria) -> Result<Page<T>, Error>;
+}
@@ -1,9 +1,33 @@
-use anyhow::{Error, Result};
+use async_trait::async_trait;
+use derive_new::new;
+use domain::error::{Error, Result};
 
-pub trait ListRepository<T> {
-    fn list(&self) -> Result<Vec<T>, Error>;
+#[derive(new, Clone, Debug, Default, PartialEq)]
+pub struct ListCriteria {
+    pub owner: Option<String>,
+    pub after: Option<String>,
+    pub limit: i64,
+    pub show_deleted: bool,
 }
 
-pub trait GetRepository<T> {
-    fn get(&self, id: String) -> Result<T, Error>;
+#[derive(new, Clone, Debug, PartialEq)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total_size: i64,
+}
+
+#[async_trait]
+pub trait ListRepository<T>: Send + Sync
+where
+    T: Send + 'static,
+{
+    async fn list(&self, criteria: ListCriteria) -> Result<Page<T>, Error>;
+}
+
+#[async_trait]
+pub trait GetRepository<T>: Send + Sync
+where
+    T: Send + 'static,
+{
+    async fn get(&self, id: String) -> Result<T, Error>;
 }
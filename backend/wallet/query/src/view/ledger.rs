@@ -0,0 +1,17 @@
+#[derive(Clone, Debug, PartialEq)]
+pub struct LedgerAccount {
+    pub account: String,
+    pub kind: String,
+    pub currency: String,
+    pub debit: String,
+    pub credit: String,
+    pub balance: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrialBalanceTotal {
+    pub currency: String,
+    pub debit: String,
+    pub credit: String,
+    pub balanced: bool,
+}
@@ -0,0 +1,14 @@
+use std::time::SystemTime;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Transaction {
+    pub id: String,
+    pub wallet_id: String,
+    pub kind: String,
+    pub amount: String,
+    pub balance: String,
+    pub currency: String,
+    pub counterparty: Option<String>,
+    pub memo: String,
+    pub create_at: SystemTime,
+}
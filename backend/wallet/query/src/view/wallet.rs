@@ -1,6 +1,14 @@
-#[derive(Clone, Debug)]
+use std::time::SystemTime;
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct Wallet {
     pub id: String,
     pub owner: String,
     pub balance: String,
+    pub currency: String,
+    pub version: i64,
+    /// ACTIVE, or DELETED while the wallet waits to be purged.
+    pub status: String,
+    pub transaction_count: i64,
+    pub last_activity_at: SystemTime,
 }
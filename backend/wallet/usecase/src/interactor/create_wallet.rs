@@ -1,50 +1,62 @@
 use crate::port::{CreateWalletInputData, CreateWalletOutputData, Port};
-use anyhow::{Error, Result};
+use async_trait::async_trait;
 use derive_new::new;
-use domain::entity::{Wallet, WalletBuilder};
-use domain::repository::IdRepository;
-use domain::repository::{CreateRepository, GetRepository};
-use domain::vo::{Money, JPY};
+use domain::entity::Wallet;
+use domain::error::{Error, Result};
+use domain::event::{EventPublisher, WalletEvent};
+use domain::repository::{CreateRepository, GetRepository, IdRepository, UnitOfWork};
+use domain::vo::{Currency, Owner};
 
 #[derive(new)]
-pub struct CreateWalletInteractor<I, S>
+pub struct CreateWalletInteractor<I, U, P>
 where
     I: IdRepository,
-    S: CreateRepository<Wallet> + GetRepository<Wallet>,
+    U: UnitOfWork,
+    P: EventPublisher<WalletEvent>,
 {
     id_repository: I,
-    wallet_repository: S,
+    unit_of_work: U,
+    event_publisher: P,
 }
 
-impl<I, S> Port<CreateWalletInputData, CreateWalletOutputData> for CreateWalletInteractor<I, S>
+#[async_trait]
+impl<I, U, P> Port<CreateWalletInputData, CreateWalletOutputData>
+    for CreateWalletInteractor<I, U, P>
 where
     I: IdRepository,
-    S: CreateRepository<Wallet> + GetRepository<Wallet>,
+    U: UnitOfWork,
+    P: EventPublisher<WalletEvent>,
 {
-    fn handle(&self, _input: CreateWalletInputData) -> Result<CreateWalletOutputData, Error> {
+    async fn handle(&self, input: CreateWalletInputData) -> Result<CreateWalletOutputData, Error> {
         let id = self.id_repository.generate::<Wallet>()?;
+        let owner = input.owner.parse::<Owner>()?;
+        let currency = input
+            .currency
+            .parse::<Currency>()
+            .map_err(|e| Error::invalid_argument("currency", e))?;
 
-        let wallet = WalletBuilder::default()
-            .id(id)
-            .balance("0".parse::<Money<JPY>>()?)
-            .build()
-            .unwrap();
+        let mut wallet = Wallet::create(id, owner, currency);
 
-        self.wallet_repository.create(wallet.clone())?;
-        match self.wallet_repository.get(wallet.id().clone()) {
-            Ok(aggregate_root) => Ok(CreateWalletOutputData::new(aggregate_root)),
-            Err(e) => Err(e),
+        let tx = self.unit_of_work.begin().await?;
+        let wallets = self.unit_of_work.wallets(&tx);
+        let result = async {
+            wallets.create(wallet.clone()).await?;
+            wallets.get(wallet.id().clone()).await
         }
+        .await;
+        let created = self.unit_of_work.finish(tx, result).await?;
+        self.event_publisher.publish(wallet.take_events());
+
+        Ok(CreateWalletOutputData::new(created))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use anyhow::bail;
-    use domain::repository::DeleteRepository;
-    use domain::vo::Id;
-    use std::collections::HashMap;
+    use domain::entity::WalletBuilder;
+    use domain::repository::InMemoryUnitOfWork;
+    use domain::vo::{Id, Money};
     use std::sync::Arc;
     use std::sync::Mutex;
 
@@ -56,70 +68,80 @@ mod tests {
         }
     }
 
-    struct MockWalletRepository {
-        store: Arc<Mutex<HashMap<Id<Wallet>, Wallet>>>,
+    struct MockEventPublisher {
+        published: Arc<Mutex<Vec<WalletEvent>>>,
     }
-    impl MockWalletRepository {
+    impl MockEventPublisher {
         fn new() -> Self {
-            let m = HashMap::new();
             Self {
-                store: Arc::new(Mutex::new(m)),
+                published: Arc::new(Mutex::new(vec![])),
             }
         }
     }
 
-    impl CreateRepository<Wallet> for MockWalletRepository {
-        fn create(&self, entity: Wallet) -> Result<(), Error> {
-            let mut m = self.store.lock().unwrap();
-            m.insert(entity.id().clone(), entity.clone());
-            Ok(())
+    impl EventPublisher<WalletEvent> for MockEventPublisher {
+        fn publish(&self, events: Vec<WalletEvent>) {
+            self.published.lock().unwrap().extend(events);
         }
     }
 
-    impl GetRepository<Wallet> for MockWalletRepository {
-        fn get(&self, id: Id<Wallet>) -> Result<Wallet, Error> {
-            let m = self.store.lock().unwrap();
-            match m.get(&id.clone()) {
-                Some(aggregate_root) => Ok(aggregate_root.clone()),
-                None => bail!("not found entity"),
-            }
-        }
-    }
-
-    impl DeleteRepository<Wallet> for MockWalletRepository {
-        fn delete(&self, entity: Wallet) -> Result<(), Error> {
-            let mut m = self.store.lock().unwrap();
-            match m.remove(&entity.id().clone()) {
-                Some(_) => Ok(()),
-                None => bail!("not found entity"),
-            }
-        }
-    }
-
-    #[test]
-    fn test_create_wallet_handle() {
+    #[tokio::test]
+    async fn test_create_wallet_handle() {
         let id_repository = MockIdRepository::new();
-        let wallet_repository = MockWalletRepository::new();
-        let sut = CreateWalletInteractor::new(id_repository, wallet_repository);
+        let unit_of_work = InMemoryUnitOfWork::new();
+        let event_publisher = MockEventPublisher::new();
+        let published = event_publisher.published.clone();
+        let sut = CreateWalletInteractor::new(id_repository, unit_of_work, event_publisher);
 
         assert_eq!(
-            sut.handle(CreateWalletInputData::new("alice".to_string()))
-                .unwrap(),
+            sut.handle(CreateWalletInputData::new(
+                "alice".to_string(),
+                "JPY".to_string()
+            ))
+            .await
+            .unwrap(),
             CreateWalletOutputData::new(
                 WalletBuilder::default()
                     .id("01F8MECHZX3TBDSZ7XRADM79XE".parse::<Id<Wallet>>().unwrap())
-                    .balance("0".parse::<Money<JPY>>().unwrap())
+                    .owner("alice".parse::<Owner>().unwrap())
+                    .balance("0 JPY".parse::<Money>().unwrap())
                     .build()
                     .unwrap(),
             )
         );
+        assert_eq!(
+            *published.lock().unwrap(),
+            vec![WalletEvent::Created {
+                id: "01F8MECHZX3TBDSZ7XRADM79XE".parse::<Id<Wallet>>().unwrap(),
+                owner: "alice".parse::<Owner>().unwrap(),
+                currency: Currency::JPY,
+            }]
+        );
 
-        // ok
+        // err: the id is taken, so the wallet is not created and nothing is published
         assert!(sut
-            .handle(CreateWalletInputData::new("alice".to_string()))
-            .is_ok());
+            .handle(CreateWalletInputData::new(
+                "alice".to_string(),
+                "JPY".to_string()
+            ))
+            .await
+            .is_err());
 
         // err
-        // assert!(sut.handle(CreateWalletInputData::new()).is_err());
+        assert!(sut
+            .handle(CreateWalletInputData::new(
+                "".to_string(),
+                "JPY".to_string()
+            ))
+            .await
+            .is_err());
+        assert!(sut
+            .handle(CreateWalletInputData::new(
+                "alice".to_string(),
+                "XXX".to_string()
+            ))
+            .await
+            .is_err());
+        assert_eq!(published.lock().unwrap().len(), 1);
     }
 }
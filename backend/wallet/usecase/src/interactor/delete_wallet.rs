@@ -1,28 +1,45 @@
+use crate::interactor::precondition::ensure_version;
 use crate::port::{DeleteWalletInputData, DeleteWalletOutputData, Port};
-use anyhow::{Error, Result};
+use async_trait::async_trait;
 use derive_new::new;
 use domain::entity::Wallet;
-use domain::repository::DeleteRepository;
-use domain::repository::GetRepository;
+use domain::error::{Error, Result};
+use domain::event::{EventPublisher, WalletEvent};
+use domain::repository::{DeleteRepository, GetRepository, UnitOfWork};
 use domain::vo::Id;
 
 #[derive(new)]
-pub struct DeleteWalletInteractor<S>
+pub struct DeleteWalletInteractor<U, P>
 where
-    S: GetRepository<Wallet> + DeleteRepository<Wallet>,
+    U: UnitOfWork,
+    P: EventPublisher<WalletEvent>,
 {
-    wallet_repository: S,
+    unit_of_work: U,
+    event_publisher: P,
 }
 
-impl<S> Port<DeleteWalletInputData, DeleteWalletOutputData> for DeleteWalletInteractor<S>
+#[async_trait]
+impl<U, P> Port<DeleteWalletInputData, DeleteWalletOutputData> for DeleteWalletInteractor<U, P>
 where
-    S: GetRepository<Wallet> + DeleteRepository<Wallet>,
+    U: UnitOfWork,
+    P: EventPublisher<WalletEvent>,
 {
-    fn handle(&self, input: DeleteWalletInputData) -> Result<DeleteWalletOutputData, Error> {
+    async fn handle(&self, input: DeleteWalletInputData) -> Result<DeleteWalletOutputData, Error> {
         let id = input.id.parse::<Id<Wallet>>()?;
 
-        let wallet = self.wallet_repository.get(id)?;
-        self.wallet_repository.delete(wallet)?;
+        let tx = self.unit_of_work.begin().await?;
+        let wallets = self.unit_of_work.wallets(&tx);
+        let result = async {
+            let mut wallet = wallets.get(id).await?;
+            ensure_version(&wallet, &input.version, "etag")?;
+            wallet.delete();
+            wallets.delete(wallet.clone()).await?;
+
+            Ok(wallet)
+        }
+        .await;
+        let mut wallet = self.unit_of_work.finish(tx, result).await?;
+        self.event_publisher.publish(wallet.take_events());
 
         Ok(DeleteWalletOutputData::new())
     }
@@ -31,91 +48,124 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use anyhow::bail;
     use domain::entity::WalletBuilder;
-    use domain::repository::CreateRepository;
-    use domain::repository::DeleteRepository;
-    use domain::repository::GetRepository;
+    use domain::repository::InMemoryUnitOfWork;
     use domain::vo::Id;
     use domain::vo::Money;
-    use domain::vo::JPY;
-    use std::collections::HashMap;
+    use domain::vo::{Owner, Version};
     use std::sync::Arc;
     use std::sync::Mutex;
 
-    struct MockWalletRepository {
-        store: Arc<Mutex<HashMap<Id<Wallet>, Wallet>>>,
+    struct MockEventPublisher {
+        published: Arc<Mutex<Vec<WalletEvent>>>,
     }
-    impl MockWalletRepository {
+    impl MockEventPublisher {
         fn new() -> Self {
-            let m = HashMap::new();
             Self {
-                store: Arc::new(Mutex::new(m)),
+                published: Arc::new(Mutex::new(vec![])),
             }
         }
     }
 
-    impl CreateRepository<Wallet> for MockWalletRepository {
-        fn create(&self, entity: Wallet) -> Result<(), Error> {
-            let mut m = self.store.lock().unwrap();
-            m.insert(entity.id().clone(), entity.clone());
-            Ok(())
+    impl EventPublisher<WalletEvent> for MockEventPublisher {
+        fn publish(&self, events: Vec<WalletEvent>) {
+            self.published.lock().unwrap().extend(events);
         }
     }
 
-    impl GetRepository<Wallet> for MockWalletRepository {
-        fn get(&self, id: Id<Wallet>) -> Result<Wallet, Error> {
-            let m = self.store.lock().unwrap();
-            match m.get(&id.clone()) {
-                Some(aggregate_root) => Ok(aggregate_root.clone()),
-                None => bail!("not found entity"),
-            }
-        }
-    }
-
-    impl DeleteRepository<Wallet> for MockWalletRepository {
-        fn delete(&self, entity: Wallet) -> Result<(), Error> {
-            let mut m = self.store.lock().unwrap();
-            match m.remove(&entity.id().clone()) {
-                Some(_) => Ok(()),
-                None => bail!("not found entity"),
-            }
-        }
-    }
-
-    #[test]
-    fn test_delete_wallet_handle() {
-        let wallet_repository = MockWalletRepository::new();
+    #[tokio::test]
+    async fn test_delete_wallet_handle() {
+        let unit_of_work = InMemoryUnitOfWork::new();
         let wallet_a = WalletBuilder::default()
             .id("01F8MECHZX3TBDSZ7XRADM79XE".parse::<Id<Wallet>>().unwrap())
-            .balance("1000".parse::<Money<JPY>>().unwrap())
+            .owner("alice".parse::<Owner>().unwrap())
+            .balance("1000 JPY".parse::<Money>().unwrap())
             .build()
             .unwrap();
         let wallet_b = WalletBuilder::default()
             .id("01F8MECHZX3TBDSZ7XRADM79XF".parse::<Id<Wallet>>().unwrap())
-            .balance("0".parse::<Money<JPY>>().unwrap())
+            .owner("alice".parse::<Owner>().unwrap())
+            .balance("0 JPY".parse::<Money>().unwrap())
             .build()
             .unwrap();
 
-        wallet_repository.create(wallet_a.clone()).unwrap();
-        wallet_repository.create(wallet_b.clone()).unwrap();
+        unit_of_work.insert(wallet_a.clone());
+        unit_of_work.insert(wallet_b.clone());
 
-        let sut = DeleteWalletInteractor::new(wallet_repository);
+        let event_publisher = MockEventPublisher::new();
+        let published = event_publisher.published.clone();
+        let sut = DeleteWalletInteractor::new(unit_of_work, event_publisher);
 
         assert_eq!(
-            sut.handle(DeleteWalletInputData::new(wallet_a.id().to_string()))
-                .unwrap(),
+            sut.handle(DeleteWalletInputData::new(
+                wallet_a.id().to_string(),
+                "1".to_string()
+            ))
+            .await
+            .unwrap(),
             DeleteWalletOutputData::new()
         );
+        assert_eq!(
+            *published.lock().unwrap(),
+            vec![WalletEvent::Deleted {
+                id: wallet_a.id().clone()
+            }]
+        );
 
         // ok
         assert!(sut
-            .handle(DeleteWalletInputData::new(wallet_b.id().to_string()))
+            .handle(DeleteWalletInputData::new(
+                wallet_b.id().to_string(),
+                "".to_string()
+            ))
+            .await
             .is_ok());
 
         // err
         assert!(sut
-            .handle(DeleteWalletInputData::new("NOTFOUND_ID".to_string()))
+            .handle(DeleteWalletInputData::new(
+                "01F8MECHZX3TBDSZ7XRADM79XZ".to_string(),
+                "".to_string()
+            ))
+            .await
             .is_err());
+        assert_eq!(published.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_delete_wallet_handle_version() {
+        let unit_of_work = InMemoryUnitOfWork::new();
+        let wallet = WalletBuilder::default()
+            .id("01F8MECHZX3TBDSZ7XRADM79XE".parse::<Id<Wallet>>().unwrap())
+            .owner("alice".parse::<Owner>().unwrap())
+            .balance("1000 JPY".parse::<Money>().unwrap())
+            .version(Version::new(3))
+            .build()
+            .unwrap();
+
+        unit_of_work.insert(wallet.clone());
+
+        let sut = DeleteWalletInteractor::new(unit_of_work, MockEventPublisher::new());
+
+        // err
+        assert!(
+            sut.handle(DeleteWalletInputData::new(
+                wallet.id().to_string(),
+                "2".to_string()
+            ))
+            .await
+            .unwrap_err()
+            .reason()
+                == "VERSION_MISMATCH"
+        );
+
+        // ok
+        assert!(sut
+            .handle(DeleteWalletInputData::new(
+                wallet.id().to_string(),
+                "3".to_string()
+            ))
+            .await
+            .is_ok());
     }
 }
@@ -1,7 +1,8 @@
 use crate::port::{GetWalletInputData, GetWalletOutputData, Port};
-use anyhow::{Error, Result};
+use async_trait::async_trait;
 use derive_new::new;
 use domain::entity::Wallet;
+use domain::error::{Error, Result};
 use domain::repository::GetRepository;
 use domain::vo::Id;
 
@@ -13,14 +14,15 @@ where
     wallet_repository: S,
 }
 
+#[async_trait]
 impl<S> Port<GetWalletInputData, GetWalletOutputData> for GetWalletInteractor<S>
 where
     S: GetRepository<Wallet>,
 {
-    fn handle(&self, input: GetWalletInputData) -> Result<GetWalletOutputData, Error> {
+    async fn handle(&self, input: GetWalletInputData) -> Result<GetWalletOutputData, Error> {
         let id = input.id.parse::<Id<Wallet>>()?;
 
-        let wallet = self.wallet_repository.get(id)?;
+        let wallet = self.wallet_repository.get(id).await?;
 
         Ok(GetWalletOutputData::new(wallet))
     }
@@ -29,14 +31,13 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use anyhow::bail;
     use domain::entity::WalletBuilder;
     use domain::repository::CreateRepository;
     use domain::repository::DeleteRepository;
     use domain::repository::GetRepository;
     use domain::vo::Id;
     use domain::vo::Money;
-    use domain::vo::JPY;
+    use domain::vo::Owner;
     use std::collections::HashMap;
     use std::sync::Arc;
     use std::sync::Mutex;
@@ -53,55 +54,61 @@ mod tests {
         }
     }
 
+    #[async_trait]
     impl CreateRepository<Wallet> for MockWalletRepository {
-        fn create(&self, entity: Wallet) -> Result<(), Error> {
+        async fn create(&self, entity: Wallet) -> Result<(), Error> {
             let mut m = self.store.lock().unwrap();
             m.insert(entity.id().clone(), entity.clone());
             Ok(())
         }
     }
 
+    #[async_trait]
     impl GetRepository<Wallet> for MockWalletRepository {
-        fn get(&self, id: Id<Wallet>) -> Result<Wallet, Error> {
+        async fn get(&self, id: Id<Wallet>) -> Result<Wallet, Error> {
             let m = self.store.lock().unwrap();
             match m.get(&id.clone()) {
                 Some(aggregate_root) => Ok(aggregate_root.clone()),
-                None => bail!("not found entity"),
+                None => Err(Error::not_found("wallet", id)),
             }
         }
     }
 
+    #[async_trait]
     impl DeleteRepository<Wallet> for MockWalletRepository {
-        fn delete(&self, entity: Wallet) -> Result<(), Error> {
+        async fn delete(&self, entity: Wallet) -> Result<(), Error> {
             let mut m = self.store.lock().unwrap();
             match m.remove(&entity.id().clone()) {
                 Some(_) => Ok(()),
-                None => bail!("not found entity"),
+                None => Err(Error::not_found("wallet", entity.id())),
             }
         }
     }
 
-    #[test]
-    fn test_get_wallet_handle() {
+    #[tokio::test]
+    async fn test_get_wallet_handle() {
         let wallet_repository = MockWalletRepository::new();
         let wallet_a = WalletBuilder::default()
             .id("01F8MECHZX3TBDSZ7XRADM79XE".parse::<Id<Wallet>>().unwrap())
-            .balance("1000".parse::<Money<JPY>>().unwrap())
+            .owner("alice".parse::<Owner>().unwrap())
+            .balance("1000 JPY".parse::<Money>().unwrap())
             .build()
             .unwrap();
         let wallet_b = WalletBuilder::default()
             .id("01F8MECHZX3TBDSZ7XRADM79XF".parse::<Id<Wallet>>().unwrap())
-            .balance("0".parse::<Money<JPY>>().unwrap())
+            .owner("alice".parse::<Owner>().unwrap())
+            .balance("0 JPY".parse::<Money>().unwrap())
             .build()
             .unwrap();
 
-        wallet_repository.create(wallet_a.clone()).unwrap();
-        wallet_repository.create(wallet_b.clone()).unwrap();
+        wallet_repository.create(wallet_a.clone()).await.unwrap();
+        wallet_repository.create(wallet_b.clone()).await.unwrap();
 
         let sut = GetWalletInteractor::new(wallet_repository);
 
         assert_eq!(
             sut.handle(GetWalletInputData::new(wallet_a.id().to_string()))
+                .await
                 .unwrap(),
             GetWalletOutputData::new(wallet_a.clone())
         );
@@ -109,11 +116,15 @@ mod tests {
         // ok
         assert!(sut
             .handle(GetWalletInputData::new(wallet_b.id().to_string()))
+            .await
             .is_ok());
 
         // err
         assert!(sut
-            .handle(GetWalletInputData::new("NOTFOUND_ID".to_string()))
+            .handle(GetWalletInputData::new(
+                "01F8MECHZX3TBDSZ7XRADM79XZ".to_string()
+            ))
+            .await
             .is_err());
     }
 }
@@ -0,0 +1,68 @@
+use crate::port::PreconditionError;
+use domain::entity::Wallet;
+use domain::error::{Error, Result};
+use domain::vo::Version;
+
+/// Fails unless `wallet` is at `expected`, which callers leave empty to write unconditionally.
+/// `field` names the request field `expected` came from.
+pub(crate) fn ensure_version(
+    wallet: &Wallet,
+    expected: &str,
+    field: &'static str,
+) -> Result<(), Error> {
+    if expected.is_empty() {
+        return Ok(());
+    }
+
+    let expected = expected
+        .parse::<Version>()
+        .map_err(|e| Error::invalid_argument(field, e))?;
+    if wallet.version() != &expected {
+        return Err(PreconditionError::VersionMismatch {
+            id: wallet.id().to_string(),
+            expected,
+            actual: *wallet.version(),
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use domain::entity::WalletBuilder;
+    use domain::vo::{Id, Money, Owner};
+
+    #[test]
+    fn test_ensure_version() {
+        let wallet = WalletBuilder::default()
+            .id("01F8MECHZX3TBDSZ7XRADM79XE".parse::<Id<Wallet>>().unwrap())
+            .owner("alice".parse::<Owner>().unwrap())
+            .balance("1000 JPY".parse::<Money>().unwrap())
+            .version(Version::new(2))
+            .build()
+            .unwrap();
+
+        assert!(ensure_version(&wallet, "", "etag").is_ok());
+        assert!(ensure_version(&wallet, "2", "etag").is_ok());
+
+        // err
+        let err = ensure_version(&wallet, "1", "etag").unwrap_err();
+        assert_eq!(err.reason(), "VERSION_MISMATCH");
+        assert_eq!(
+            err.to_string(),
+            PreconditionError::VersionMismatch {
+                id: wallet.id().to_string(),
+                expected: Version::new(1),
+                actual: Version::new(2),
+            }
+            .to_string()
+        );
+        assert!(matches!(
+            ensure_version(&wallet, "x", "etag").unwrap_err(),
+            Error::InvalidArgument { field: "etag", .. }
+        ));
+    }
+}
@@ -0,0 +1,88 @@
+use crate::port::{Port, PurgeWalletsInputData, PurgeWalletsOutputData};
+use async_trait::async_trait;
+use derive_new::new;
+use domain::entity::Wallet;
+use domain::error::{Error, Result};
+use domain::repository::PurgeRepository;
+use std::time::{Duration, SystemTime};
+
+#[derive(new)]
+pub struct PurgeWalletsInteractor<S>
+where
+    S: PurgeRepository<Wallet>,
+{
+    wallet_repository: S,
+    retention: Duration,
+}
+
+#[async_trait]
+impl<S> Port<PurgeWalletsInputData, PurgeWalletsOutputData> for PurgeWalletsInteractor<S>
+where
+    S: PurgeRepository<Wallet>,
+{
+    async fn handle(&self, _: PurgeWalletsInputData) -> Result<PurgeWalletsOutputData, Error> {
+        let deleted_before = SystemTime::now() - self.retention;
+        let purged = self.wallet_repository.purge(deleted_before).await?;
+
+        Ok(PurgeWalletsOutputData::new(purged))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use domain::vo::Id;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    struct MockWalletRepository {
+        deleted: Arc<Mutex<HashMap<Id<Wallet>, SystemTime>>>,
+    }
+    impl MockWalletRepository {
+        fn new() -> Self {
+            let m = HashMap::new();
+            Self {
+                deleted: Arc::new(Mutex::new(m)),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl PurgeRepository<Wallet> for MockWalletRepository {
+        async fn purge(&self, deleted_before: SystemTime) -> Result<usize, Error> {
+            let mut m = self.deleted.lock().unwrap();
+            let before = m.len();
+            m.retain(|_, deleted_at| *deleted_at >= deleted_before);
+            Ok(before - m.len())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_purge_wallets_handle() {
+        let wallet_repository = MockWalletRepository::new();
+        let day = Duration::from_secs(24 * 60 * 60);
+        {
+            let mut m = wallet_repository.deleted.lock().unwrap();
+            m.insert(
+                "01F8MECHZX3TBDSZ7XRADM79XE".parse().unwrap(),
+                SystemTime::now(),
+            );
+            m.insert(
+                "01F8MECHZX3TBDSZ7XRADM79XF".parse().unwrap(),
+                SystemTime::now() - 2 * day,
+            );
+        }
+
+        let sut = PurgeWalletsInteractor::new(wallet_repository, day);
+
+        assert_eq!(
+            sut.handle(PurgeWalletsInputData::new()).await.unwrap(),
+            PurgeWalletsOutputData::new(1)
+        );
+        assert_eq!(
+            sut.handle(PurgeWalletsInputData::new()).await.unwrap(),
+            PurgeWalletsOutputData::new(0)
+        );
+    }
+}
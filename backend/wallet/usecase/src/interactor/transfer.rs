@@ -0,0 +1,336 @@
+use crate::interactor::precondition::ensure_version;
+use crate::port::{Port, TransferInputData, TransferOutputData};
+use async_trait::async_trait;
+use derive_new::new;
+use domain::entity::{Transaction, Wallet};
+use domain::error::{Error, Result};
+use domain::event::{EventPublisher, WalletEvent};
+use domain::repository::{IdRepository, TransferRepository};
+use domain::service::TransferService;
+use domain::vo::{Id, Memo, Money};
+use rust_decimal::Decimal;
+
+#[derive(new)]
+pub struct TransferInteractor<I, S, P>
+where
+    I: IdRepository,
+    S: TransferRepository<Wallet>,
+    P: EventPublisher<WalletEvent>,
+{
+    transfer_service: TransferService,
+    id_repository: I,
+    wallet_repository: S,
+    event_publisher: P,
+}
+
+#[async_trait]
+impl<I, S, P> Port<TransferInputData, TransferOutputData> for TransferInteractor<I, S, P>
+where
+    I: IdRepository,
+    S: TransferRepository<Wallet>,
+    P: EventPublisher<WalletEvent>,
+{
+    async fn handle(&self, input: TransferInputData) -> Result<TransferOutputData, Error> {
+        let from = input
+            .from
+            .parse::<Id<Wallet>>()
+            .map_err(|e| Error::invalid_argument("from_id", e))?;
+        let to = input
+            .to
+            .parse::<Id<Wallet>>()
+            .map_err(|e| Error::invalid_argument("to_id", e))?;
+        let amount = input
+            .amount
+            .parse::<Decimal>()
+            .map_err(|e| Error::invalid_argument("amount", e))?;
+        let memo = input.memo.parse::<Memo>()?;
+        let withdrawn = self.id_repository.generate::<Transaction>()?;
+        let deposited = self.id_repository.generate::<Transaction>()?;
+
+        let transfer_service = self.transfer_service.clone();
+
+        let (mut from, mut to) = self
+            .wallet_repository
+            .transfer(from, to, move |from, to| {
+                ensure_version(from, &input.from_version, "from_etag")?;
+                ensure_version(to, &input.to_version, "to_etag")?;
+                let amount = Money::new(amount, from.balance().currency())?;
+                transfer_service.transfer(from, to, amount, withdrawn, deposited, memo)
+            })
+            .await?;
+        let mut events = from.take_events();
+        events.extend(to.take_events());
+        self.event_publisher.publish(events);
+
+        Ok(TransferOutputData::new(from, to))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use domain::entity::WalletBuilder;
+    use domain::repository::{CreateRepository, GetRepository};
+    use domain::vo::{Owner, TransactionKind, Version};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    #[derive(new)]
+    struct MockIdRepository {}
+    impl IdRepository for MockIdRepository {
+        fn generate<T>(&self) -> Result<Id<T>, Error> {
+            Ok("01F8MECHZX3TBDSZ7XRADM79XA".parse::<Id<T>>().unwrap())
+        }
+    }
+
+    struct MockWalletRepository {
+        store: Arc<Mutex<HashMap<Id<Wallet>, Wallet>>>,
+        ledger: Arc<Mutex<Vec<Transaction>>>,
+    }
+    impl MockWalletRepository {
+        fn new() -> Self {
+            let m = HashMap::new();
+            Self {
+                store: Arc::new(Mutex::new(m)),
+                ledger: Arc::new(Mutex::new(vec![])),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl CreateRepository<Wallet> for MockWalletRepository {
+        async fn create(&self, entity: Wallet) -> Result<(), Error> {
+            let mut m = self.store.lock().unwrap();
+            m.insert(entity.id().clone(), entity.clone());
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl GetRepository<Wallet> for MockWalletRepository {
+        async fn get(&self, id: Id<Wallet>) -> Result<Wallet, Error> {
+            let m = self.store.lock().unwrap();
+            match m.get(&id.clone()) {
+                Some(aggregate_root) => Ok(aggregate_root.clone()),
+                None => Err(Error::not_found("wallet", id)),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl TransferRepository<Wallet> for MockWalletRepository {
+        async fn transfer<F>(
+            &self,
+            from: Id<Wallet>,
+            to: Id<Wallet>,
+            f: F,
+        ) -> Result<(Wallet, Wallet), Error>
+        where
+            F: FnOnce(&mut Wallet, &mut Wallet) -> Result<(), Error> + Send + 'static,
+        {
+            let mut m = self.store.lock().unwrap();
+            let (mut from, mut to) = match (m.get(&from), m.get(&to)) {
+                (Some(from), Some(to)) => (from.clone(), to.clone()),
+                _ => return Err(Error::not_found("wallet", from)),
+            };
+
+            f(&mut from, &mut to)?;
+
+            let mut ledger = self.ledger.lock().unwrap();
+            ledger.append(&mut from.take_transactions());
+            ledger.append(&mut to.take_transactions());
+            from.set_version(from.version().next());
+            to.set_version(to.version().next());
+            m.insert(from.id().clone(), from.clone());
+            m.insert(to.id().clone(), to.clone());
+            Ok((from, to))
+        }
+    }
+
+    struct MockEventPublisher {
+        published: Arc<Mutex<Vec<WalletEvent>>>,
+    }
+    impl MockEventPublisher {
+        fn new() -> Self {
+            Self {
+                published: Arc::new(Mutex::new(vec![])),
+            }
+        }
+    }
+
+    impl EventPublisher<WalletEvent> for MockEventPublisher {
+        fn publish(&self, events: Vec<WalletEvent>) {
+            self.published.lock().unwrap().extend(events);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transfer_handle() {
+        let wallet_repository = MockWalletRepository::new();
+        let wallet_a = WalletBuilder::default()
+            .id("01F8MECHZX3TBDSZ7XRADM79XE".parse::<Id<Wallet>>().unwrap())
+            .owner("alice".parse::<Owner>().unwrap())
+            .balance("1000 JPY".parse::<Money>().unwrap())
+            .build()
+            .unwrap();
+        let wallet_b = WalletBuilder::default()
+            .id("01F8MECHZX3TBDSZ7XRADM79XF".parse::<Id<Wallet>>().unwrap())
+            .owner("alice".parse::<Owner>().unwrap())
+            .balance("0 JPY".parse::<Money>().unwrap())
+            .build()
+            .unwrap();
+
+        wallet_repository.create(wallet_a.clone()).await.unwrap();
+        wallet_repository.create(wallet_b.clone()).await.unwrap();
+        let ledger = wallet_repository.ledger.clone();
+        let event_publisher = MockEventPublisher::new();
+        let published = event_publisher.published.clone();
+
+        let sut = TransferInteractor::new(
+            TransferService::default(),
+            MockIdRepository::new(),
+            wallet_repository,
+            event_publisher,
+        );
+
+        let output = sut
+            .handle(TransferInputData::new(
+                wallet_a.id().to_string(),
+                wallet_b.id().to_string(),
+                "300".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(output.from.balance(), &"700 JPY".parse::<Money>().unwrap());
+        assert_eq!(output.to.balance(), &"300 JPY".parse::<Money>().unwrap());
+        {
+            let ledger = ledger.lock().unwrap();
+            assert_eq!(ledger.len(), 2);
+            assert_eq!(ledger[0].wallet_id(), wallet_a.id());
+            assert_eq!(ledger[0].kind(), &TransactionKind::TransferOut);
+            assert_eq!(ledger[0].counterparty(), &Some(wallet_b.id().clone()));
+            assert_eq!(ledger[0].balance(), &"700 JPY".parse::<Money>().unwrap());
+            assert_eq!(ledger[1].wallet_id(), wallet_b.id());
+            assert_eq!(ledger[1].kind(), &TransactionKind::TransferIn);
+            assert_eq!(ledger[1].counterparty(), &Some(wallet_a.id().clone()));
+            assert_eq!(ledger[1].balance(), &"300 JPY".parse::<Money>().unwrap());
+        }
+        {
+            let published = published.lock().unwrap();
+            assert_eq!(published.len(), 2);
+            assert_eq!(published[0].name(), "WalletWithdrawn");
+            assert_eq!(published[0].id(), wallet_a.id());
+            assert_eq!(published[1].name(), "WalletDeposited");
+            assert_eq!(published[1].id(), wallet_b.id());
+        }
+
+        // ok
+        assert!(sut
+            .handle(TransferInputData::new(
+                wallet_b.id().to_string(),
+                wallet_a.id().to_string(),
+                "300".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+            ))
+            .await
+            .is_ok());
+
+        // err
+        assert!(sut
+            .handle(TransferInputData::new(
+                wallet_b.id().to_string(),
+                wallet_a.id().to_string(),
+                "1".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+            ))
+            .await
+            .is_err());
+        assert!(sut
+            .handle(TransferInputData::new(
+                wallet_a.id().to_string(),
+                wallet_a.id().to_string(),
+                "1".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+            ))
+            .await
+            .is_err());
+        assert!(sut
+            .handle(TransferInputData::new(
+                wallet_a.id().to_string(),
+                "01F8MECHZX3TBDSZ7XRADM79XZ".to_string(),
+                "1".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+            ))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_transfer_handle_version() {
+        let wallet_repository = MockWalletRepository::new();
+        let wallet_a = WalletBuilder::default()
+            .id("01F8MECHZX3TBDSZ7XRADM79XE".parse::<Id<Wallet>>().unwrap())
+            .owner("alice".parse::<Owner>().unwrap())
+            .balance("1000 JPY".parse::<Money>().unwrap())
+            .build()
+            .unwrap();
+        let wallet_b = WalletBuilder::default()
+            .id("01F8MECHZX3TBDSZ7XRADM79XF".parse::<Id<Wallet>>().unwrap())
+            .owner("alice".parse::<Owner>().unwrap())
+            .balance("0 JPY".parse::<Money>().unwrap())
+            .build()
+            .unwrap();
+
+        wallet_repository.create(wallet_a.clone()).await.unwrap();
+        wallet_repository.create(wallet_b.clone()).await.unwrap();
+
+        let sut = TransferInteractor::new(
+            TransferService::default(),
+            MockIdRepository::new(),
+            wallet_repository,
+            MockEventPublisher::new(),
+        );
+
+        let output = sut
+            .handle(TransferInputData::new(
+                wallet_a.id().to_string(),
+                wallet_b.id().to_string(),
+                "300".to_string(),
+                "1".to_string(),
+                "1".to_string(),
+                "".to_string(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(output.from.version(), &Version::new(2));
+        assert_eq!(output.to.version(), &Version::new(2));
+
+        // err
+        assert!(
+            sut.handle(TransferInputData::new(
+                wallet_a.id().to_string(),
+                wallet_b.id().to_string(),
+                "300".to_string(),
+                "2".to_string(),
+                "1".to_string(),
+                "".to_string(),
+            ))
+            .await
+            .unwrap_err()
+            .reason()
+                == "VERSION_MISMATCH"
+        );
+    }
+}
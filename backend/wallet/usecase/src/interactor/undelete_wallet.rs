@@ -0,0 +1,142 @@
+use crate::port::{Port, UndeleteWalletError, UndeleteWalletInputData, UndeleteWalletOutputData};
+use async_trait::async_trait;
+use derive_new::new;
+use domain::entity::Wallet;
+use domain::error::{Error, Result};
+use domain::event::{EventPublisher, WalletEvent};
+use domain::repository::{UndeleteRepository, UnitOfWork};
+use domain::vo::Id;
+use std::time::{Duration, SystemTime};
+
+#[derive(new)]
+pub struct UndeleteWalletInteractor<U, P>
+where
+    U: UnitOfWork,
+    P: EventPublisher<WalletEvent>,
+{
+    unit_of_work: U,
+    event_publisher: P,
+    retention: Duration,
+}
+
+#[async_trait]
+impl<U, P> Port<UndeleteWalletInputData, UndeleteWalletOutputData>
+    for UndeleteWalletInteractor<U, P>
+where
+    U: UnitOfWork,
+    P: EventPublisher<WalletEvent>,
+{
+    async fn handle(
+        &self,
+        input: UndeleteWalletInputData,
+    ) -> Result<UndeleteWalletOutputData, Error> {
+        let id = input.id.parse::<Id<Wallet>>()?;
+
+        let tx = self.unit_of_work.begin().await?;
+        let wallets = self.unit_of_work.wallets(&tx);
+        let result = async {
+            let (mut wallet, deleted_at) = wallets.get_deleted(id).await?;
+            let elapsed = SystemTime::now()
+                .duration_since(deleted_at)
+                .unwrap_or_default();
+            if elapsed > self.retention {
+                return Err(UndeleteWalletError::RetentionExpired(wallet.id().to_string()).into());
+            }
+
+            wallet.undelete();
+            wallets.undelete(wallet).await
+        }
+        .await;
+        let mut wallet = self.unit_of_work.finish(tx, result).await?;
+        self.event_publisher.publish(wallet.take_events());
+
+        Ok(UndeleteWalletOutputData::new(wallet))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use domain::entity::WalletBuilder;
+    use domain::repository::InMemoryUnitOfWork;
+    use domain::vo::Money;
+    use domain::vo::Owner;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    struct MockEventPublisher {
+        published: Arc<Mutex<Vec<WalletEvent>>>,
+    }
+    impl MockEventPublisher {
+        fn new() -> Self {
+            Self {
+                published: Arc::new(Mutex::new(vec![])),
+            }
+        }
+    }
+
+    impl EventPublisher<WalletEvent> for MockEventPublisher {
+        fn publish(&self, events: Vec<WalletEvent>) {
+            self.published.lock().unwrap().extend(events);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_undelete_wallet_handle() {
+        let unit_of_work = InMemoryUnitOfWork::new();
+        let wallet_a = WalletBuilder::default()
+            .id("01F8MECHZX3TBDSZ7XRADM79XE".parse::<Id<Wallet>>().unwrap())
+            .owner("alice".parse::<Owner>().unwrap())
+            .balance("1000 JPY".parse::<Money>().unwrap())
+            .build()
+            .unwrap();
+        let wallet_b = WalletBuilder::default()
+            .id("01F8MECHZX3TBDSZ7XRADM79XF".parse::<Id<Wallet>>().unwrap())
+            .owner("alice".parse::<Owner>().unwrap())
+            .balance("0 JPY".parse::<Money>().unwrap())
+            .build()
+            .unwrap();
+
+        let day = Duration::from_secs(24 * 60 * 60);
+        unit_of_work.insert_deleted(wallet_a.clone(), SystemTime::now());
+        unit_of_work.insert_deleted(wallet_b.clone(), SystemTime::now() - 2 * day);
+
+        let event_publisher = MockEventPublisher::new();
+        let published = event_publisher.published.clone();
+        let sut = UndeleteWalletInteractor::new(unit_of_work, event_publisher, day);
+
+        // ok
+        assert_eq!(
+            sut.handle(UndeleteWalletInputData::new(wallet_a.id().to_string()))
+                .await
+                .unwrap(),
+            UndeleteWalletOutputData::new(wallet_a.clone())
+        );
+        assert_eq!(
+            *published.lock().unwrap(),
+            vec![WalletEvent::Undeleted {
+                id: wallet_a.id().clone()
+            }]
+        );
+
+        // err: past the retention window
+        let err = sut
+            .handle(UndeleteWalletInputData::new(wallet_b.id().to_string()))
+            .await
+            .unwrap_err();
+        assert_eq!(err.reason(), "RETENTION_EXPIRED");
+        assert_eq!(
+            err.to_string(),
+            UndeleteWalletError::RetentionExpired(wallet_b.id().to_string()).to_string()
+        );
+
+        // err: not deleted
+        assert!(sut
+            .handle(UndeleteWalletInputData::new(
+                "01F8MECHZX3TBDSZ7XRADM79XZ".to_string()
+            ))
+            .await
+            .is_err());
+        assert_eq!(published.lock().unwrap().len(), 1);
+    }
+}
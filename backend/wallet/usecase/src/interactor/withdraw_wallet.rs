@@ -0,0 +1,257 @@
+use crate::interactor::precondition::ensure_version;
+use crate::port::{Port, WithdrawWalletInputData, WithdrawWalletOutputData};
+use async_trait::async_trait;
+use derive_new::new;
+use domain::entity::{Transaction, Wallet};
+use domain::error::{Error, Result};
+use domain::event::{EventPublisher, WalletEvent};
+use domain::repository::{GetRepository, IdRepository, UnitOfWork, UpdateRepository};
+use domain::vo::{Id, Memo, Money};
+use rust_decimal::Decimal;
+
+#[derive(new)]
+pub struct WithdrawWalletInteractor<I, U, P>
+where
+    I: IdRepository,
+    U: UnitOfWork,
+    P: EventPublisher<WalletEvent>,
+{
+    id_repository: I,
+    unit_of_work: U,
+    event_publisher: P,
+}
+
+#[async_trait]
+impl<I, U, P> Port<WithdrawWalletInputData, WithdrawWalletOutputData>
+    for WithdrawWalletInteractor<I, U, P>
+where
+    I: IdRepository,
+    U: UnitOfWork,
+    P: EventPublisher<WalletEvent>,
+{
+    async fn handle(
+        &self,
+        input: WithdrawWalletInputData,
+    ) -> Result<WithdrawWalletOutputData, Error> {
+        let id = input.id.parse::<Id<Wallet>>()?;
+        let amount = input
+            .amount
+            .parse::<Decimal>()
+            .map_err(|e| Error::invalid_argument("amount", e))?;
+        let memo = input.memo.parse::<Memo>()?;
+
+        let tx = self.unit_of_work.begin().await?;
+        let wallets = self.unit_of_work.wallets(&tx);
+        let result = async {
+            let mut wallet = wallets.get(id).await?;
+            ensure_version(&wallet, &input.version, "etag")?;
+            let amount = Money::new(amount, wallet.balance().currency())?;
+            wallet.withdraw(self.id_repository.generate::<Transaction>()?, amount, memo)?;
+            wallets.update(wallet).await
+        }
+        .await;
+        let mut wallet = self.unit_of_work.finish(tx, result).await?;
+        self.event_publisher.publish(wallet.take_events());
+
+        Ok(WithdrawWalletOutputData::new(wallet))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::port::PreconditionError;
+    use domain::entity::WalletBuilder;
+    use domain::repository::InMemoryUnitOfWork;
+    use domain::vo::{Owner, Version};
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    #[derive(new)]
+    struct MockIdRepository {}
+    impl IdRepository for MockIdRepository {
+        fn generate<T>(&self) -> Result<Id<T>, Error> {
+            Ok("01F8MECHZX3TBDSZ7XRADM79XA".parse::<Id<T>>().unwrap())
+        }
+    }
+
+    struct MockEventPublisher {
+        published: Arc<Mutex<Vec<WalletEvent>>>,
+    }
+    impl MockEventPublisher {
+        fn new() -> Self {
+            Self {
+                published: Arc::new(Mutex::new(vec![])),
+            }
+        }
+    }
+
+    impl EventPublisher<WalletEvent> for MockEventPublisher {
+        fn publish(&self, events: Vec<WalletEvent>) {
+            self.published.lock().unwrap().extend(events);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_withdraw_wallet_handle() {
+        let unit_of_work = InMemoryUnitOfWork::new();
+        let wallet = WalletBuilder::default()
+            .id("01F8MECHZX3TBDSZ7XRADM79XE".parse::<Id<Wallet>>().unwrap())
+            .owner("alice".parse::<Owner>().unwrap())
+            .balance("1000 JPY".parse::<Money>().unwrap())
+            .build()
+            .unwrap();
+
+        unit_of_work.insert(wallet.clone());
+
+        let event_publisher = MockEventPublisher::new();
+        let published = event_publisher.published.clone();
+        let sut =
+            WithdrawWalletInteractor::new(MockIdRepository::new(), unit_of_work, event_publisher);
+
+        assert_eq!(
+            sut.handle(WithdrawWalletInputData::new(
+                wallet.id().to_string(),
+                "400".to_string(),
+                "".to_string(),
+                "".to_string()
+            ))
+            .await
+            .unwrap()
+            .wallet
+            .balance(),
+            &"600 JPY".parse::<Money>().unwrap()
+        );
+        {
+            let published = published.lock().unwrap();
+            assert_eq!(published.len(), 1);
+            assert!(matches!(
+                &published[0],
+                WalletEvent::Withdrawn { id, amount, .. }
+                    if id == wallet.id() && amount == &"400 JPY".parse::<Money>().unwrap()
+            ));
+        }
+
+        // ok
+        assert_eq!(
+            sut.handle(WithdrawWalletInputData::new(
+                wallet.id().to_string(),
+                "600".to_string(),
+                "".to_string(),
+                "".to_string()
+            ))
+            .await
+            .unwrap()
+            .wallet
+            .balance(),
+            &"0 JPY".parse::<Money>().unwrap()
+        );
+
+        // err
+        assert!(sut
+            .handle(WithdrawWalletInputData::new(
+                wallet.id().to_string(),
+                "1".to_string(),
+                "".to_string(),
+                "".to_string()
+            ))
+            .await
+            .is_err());
+        assert!(sut
+            .handle(WithdrawWalletInputData::new(
+                wallet.id().to_string(),
+                "0".to_string(),
+                "".to_string(),
+                "".to_string()
+            ))
+            .await
+            .is_err());
+        assert!(sut
+            .handle(WithdrawWalletInputData::new(
+                wallet.id().to_string(),
+                "0.5".to_string(),
+                "".to_string(),
+                "".to_string()
+            ))
+            .await
+            .is_err());
+        assert!(sut
+            .handle(WithdrawWalletInputData::new(
+                "01F8MECHZX3TBDSZ7XRADM79XZ".to_string(),
+                "100".to_string(),
+                "".to_string(),
+                "".to_string()
+            ))
+            .await
+            .is_err());
+
+        // err: stale version
+        let err = sut
+            .handle(WithdrawWalletInputData::new(
+                wallet.id().to_string(),
+                "1".to_string(),
+                "1".to_string(),
+                "".to_string(),
+            ))
+            .await
+            .unwrap_err();
+        assert_eq!(err.reason(), "VERSION_MISMATCH");
+    }
+
+    #[tokio::test]
+    async fn test_withdraw_wallet_handle_version() {
+        let unit_of_work = InMemoryUnitOfWork::new();
+        let wallet = WalletBuilder::default()
+            .id("01F8MECHZX3TBDSZ7XRADM79XE".parse::<Id<Wallet>>().unwrap())
+            .owner("alice".parse::<Owner>().unwrap())
+            .balance("1000 JPY".parse::<Money>().unwrap())
+            .build()
+            .unwrap();
+
+        unit_of_work.insert(wallet.clone());
+
+        let sut = WithdrawWalletInteractor::new(
+            MockIdRepository::new(),
+            unit_of_work,
+            MockEventPublisher::new(),
+        );
+
+        let output = sut
+            .handle(WithdrawWalletInputData::new(
+                wallet.id().to_string(),
+                "100".to_string(),
+                "1".to_string(),
+                "".to_string(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(output.wallet.version(), &Version::new(2));
+
+        assert!(sut
+            .handle(WithdrawWalletInputData::new(
+                wallet.id().to_string(),
+                "100".to_string(),
+                "2".to_string(),
+                "".to_string(),
+            ))
+            .await
+            .is_ok());
+        assert_eq!(
+            sut.handle(WithdrawWalletInputData::new(
+                wallet.id().to_string(),
+                "100".to_string(),
+                "2".to_string(),
+                "".to_string(),
+            ))
+            .await
+            .unwrap_err()
+            .to_string(),
+            PreconditionError::VersionMismatch {
+                id: wallet.id().to_string(),
+                expected: Version::new(2),
+                actual: Version::new(3),
+            }
+            .to_string()
+        );
+    }
+}
@@ -5,6 +5,7 @@ use domain::entity::Wallet;
 #[derive(new, Clone, Debug, PartialEq)]
 pub struct CreateWalletInputData {
     pub owner: String,
+    pub currency: String,
 }
 
 impl InputData for CreateWalletInputData {}
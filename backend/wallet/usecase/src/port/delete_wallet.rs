@@ -4,6 +4,7 @@ use derive_new::new;
 #[derive(new, Clone, Debug, PartialEq)]
 pub struct DeleteWalletInputData {
     pub id: String,
+    pub version: String,
 }
 
 impl InputData for DeleteWalletInputData {}
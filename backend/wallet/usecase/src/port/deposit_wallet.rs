@@ -0,0 +1,20 @@
+use crate::port::{InputData, OutputData};
+use derive_new::new;
+use domain::entity::Wallet;
+
+#[derive(new, Clone, Debug, PartialEq)]
+pub struct DepositWalletInputData {
+    pub id: String,
+    pub amount: String,
+    pub version: String,
+    pub memo: String,
+}
+
+impl InputData for DepositWalletInputData {}
+
+#[derive(new, Clone, Debug, PartialEq)]
+pub struct DepositWalletOutputData {
+    pub wallet: Wallet,
+}
+
+impl OutputData for DepositWalletOutputData {}
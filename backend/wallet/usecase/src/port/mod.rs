@@ -3,9 +3,21 @@ mod port;
 
 mod create_wallet;
 mod delete_wallet;
+mod deposit_wallet;
 mod get_wallet;
+mod precondition;
+mod purge_wallets;
+mod transfer;
+mod undelete_wallet;
+mod withdraw_wallet;
 
 pub use self::create_wallet::*;
 pub use self::delete_wallet::*;
+pub use self::deposit_wallet::*;
 pub use self::get_wallet::*;
 pub use self::port::*;
+pub use self::precondition::*;
+pub use self::purge_wallets::*;
+pub use self::transfer::*;
+pub use self::undelete_wallet::*;
+pub use self::withdraw_wallet::*;
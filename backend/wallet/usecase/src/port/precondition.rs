@@ -0,0 +1,19 @@
+use domain::error::Error;
+use domain::vo::Version;
+use thiserror::Error;
+
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum PreconditionError {
+    #[error("{id} is at version {actual}, but version {expected} was expected")]
+    VersionMismatch {
+        id: String,
+        expected: Version,
+        actual: Version,
+    },
+}
+
+impl From<PreconditionError> for Error {
+    fn from(e: PreconditionError) -> Self {
+        Self::failed_precondition("VERSION_MISMATCH", e)
+    }
+}
@@ -0,0 +1,14 @@
+use crate::port::{InputData, OutputData};
+use derive_new::new;
+
+#[derive(new, Clone, Debug, PartialEq)]
+pub struct PurgeWalletsInputData {}
+
+impl InputData for PurgeWalletsInputData {}
+
+#[derive(new, Clone, Debug, PartialEq)]
+pub struct PurgeWalletsOutputData {
+    pub purged: usize,
+}
+
+impl OutputData for PurgeWalletsOutputData {}
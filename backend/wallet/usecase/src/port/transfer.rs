@@ -0,0 +1,23 @@
+use crate::port::{InputData, OutputData};
+use derive_new::new;
+use domain::entity::Wallet;
+
+#[derive(new, Clone, Debug, PartialEq)]
+pub struct TransferInputData {
+    pub from: String,
+    pub to: String,
+    pub amount: String,
+    pub from_version: String,
+    pub to_version: String,
+    pub memo: String,
+}
+
+impl InputData for TransferInputData {}
+
+#[derive(new, Clone, Debug, PartialEq)]
+pub struct TransferOutputData {
+    pub from: Wallet,
+    pub to: Wallet,
+}
+
+impl OutputData for TransferOutputData {}
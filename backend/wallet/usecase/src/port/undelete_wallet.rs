@@ -0,0 +1,31 @@
+use crate::port::{InputData, OutputData};
+use derive_new::new;
+use domain::entity::Wallet;
+use domain::error::Error;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum UndeleteWalletError {
+    #[error("wallet {0} is past its retention window and can no longer be undeleted")]
+    RetentionExpired(String),
+}
+
+impl From<UndeleteWalletError> for Error {
+    fn from(e: UndeleteWalletError) -> Self {
+        Self::failed_precondition("RETENTION_EXPIRED", e)
+    }
+}
+
+#[derive(new, Clone, Debug, PartialEq)]
+pub struct UndeleteWalletInputData {
+    pub id: String,
+}
+
+impl InputData for UndeleteWalletInputData {}
+
+#[derive(new, Clone, Debug, PartialEq)]
+pub struct UndeleteWalletOutputData {
+    pub wallet: Wallet,
+}
+
+impl OutputData for UndeleteWalletOutputData {}
@@ -0,0 +1,20 @@
+use crate::port::{InputData, OutputData};
+use derive_new::new;
+use domain::entity::Wallet;
+
+#[derive(new, Clone, Debug, PartialEq)]
+pub struct WithdrawWalletInputData {
+    pub id: String,
+    pub amount: String,
+    pub version: String,
+    pub memo: String,
+}
+
+impl InputData for WithdrawWalletInputData {}
+
+#[derive(new, Clone, Debug, PartialEq)]
+pub struct WithdrawWalletOutputData {
+    pub wallet: Wallet,
+}
+
+impl OutputData for WithdrawWalletOutputData {}